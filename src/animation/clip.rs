@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use keyframe::{AnimationSequence, Keyframe};
+use tetra::graphics::{Color, DrawParams};
+use tetra::math::Vec2;
+use tetra::{Context, time};
+use crate::animation::values::{TweenVec2, TweenColor};
+use crate::TetraVec2;
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub enum PlaybackMode {
+	/// play from start to end and stop
+	Once,
+	/// start again at the beginning after the end
+	Loop,
+	/// play forward and backward in turns
+	PingPong,
+	/// play from end to start and stop
+	Reverse,
+}
+
+/// Animation with several tracks (position, rotation, scale, color and custom values)
+/// keyframe times are in seconds
+pub struct AnimationClip {
+	position: Option<AnimationSequence<TweenVec2>>,
+	rotation: Option<AnimationSequence<f32>>,
+	scale: Option<AnimationSequence<TweenVec2>>,
+	color: Option<AnimationSequence<TweenColor>>,
+	custom: HashMap<String, AnimationSequence<f32>>,
+	mode: PlaybackMode,
+	elapsed: f64,
+}
+
+#[allow(dead_code)]
+impl AnimationClip {
+	pub fn new() -> AnimationClip{
+		AnimationClip{
+			position: None,
+			rotation: None,
+			scale: None,
+			color: None,
+			custom: HashMap::new(),
+			mode: PlaybackMode::Once,
+			elapsed: 0.0,
+		}
+	}
+
+	pub fn position(mut self, keyframes: Vec<Keyframe<TweenVec2>>) -> Self{
+		self.position = Some(AnimationSequence::from(keyframes));
+		self.advance_tracks();
+		self
+	}
+
+	pub fn rotation(mut self, keyframes: Vec<Keyframe<f32>>) -> Self{
+		self.rotation = Some(AnimationSequence::from(keyframes));
+		self.advance_tracks();
+		self
+	}
+
+	pub fn scale(mut self, keyframes: Vec<Keyframe<TweenVec2>>) -> Self{
+		self.scale = Some(AnimationSequence::from(keyframes));
+		self.advance_tracks();
+		self
+	}
+
+	pub fn color(mut self, keyframes: Vec<Keyframe<TweenColor>>) -> Self{
+		self.color = Some(AnimationSequence::from(keyframes));
+		self.advance_tracks();
+		self
+	}
+
+	/// add a custom f32 track which can be read with get_value(name)
+	pub fn custom(mut self, name: &str, keyframes: Vec<Keyframe<f32>>) -> Self{
+		self.custom.insert(name.to_string(), AnimationSequence::from(keyframes));
+		self.advance_tracks();
+		self
+	}
+
+	pub fn mode(mut self, mode: PlaybackMode) -> Self{
+		self.mode = mode;
+		self.advance_tracks();
+		self
+	}
+
+	pub fn set_mode(&mut self, mode: PlaybackMode){
+		self.mode = mode;
+		self.advance_tracks();
+	}
+
+	/// length of the longest track in seconds
+	pub fn duration(&self) -> f64{
+		let mut duration = 0.0_f64;
+		if let Some(s) = self.position.as_ref(){
+			duration = duration.max(s.duration());
+		}
+		if let Some(s) = self.rotation.as_ref(){
+			duration = duration.max(s.duration());
+		}
+		if let Some(s) = self.scale.as_ref(){
+			duration = duration.max(s.duration());
+		}
+		if let Some(s) = self.color.as_ref(){
+			duration = duration.max(s.duration());
+		}
+		for s in self.custom.values(){
+			duration = duration.max(s.duration());
+		}
+		duration
+	}
+
+	/// current position of the playhead in seconds
+	pub fn time(&self) -> f64{
+		let duration = self.duration();
+		if duration <= 0.0{
+			return 0.0;
+		}
+		match self.mode{
+			PlaybackMode::Once => self.elapsed.min(duration),
+			PlaybackMode::Reverse => duration - self.elapsed.min(duration),
+			PlaybackMode::Loop => self.elapsed % duration,
+			PlaybackMode::PingPong => {
+				let time = self.elapsed % (duration * 2.0);
+				if time <= duration{
+					time
+				}else{
+					duration * 2.0 - time
+				}
+			}
+		}
+	}
+
+	/// progress of the current cycle between 0 and 1 (a ping-pong cycle is forward and backward)
+	pub fn progress(&self) -> f32{
+		let duration = self.duration();
+		if duration <= 0.0{
+			return 1.0;
+		}
+		let progress = match self.mode{
+			PlaybackMode::Once | PlaybackMode::Reverse => self.elapsed.min(duration) / duration,
+			PlaybackMode::Loop => (self.elapsed % duration) / duration,
+			PlaybackMode::PingPong => (self.elapsed % (duration * 2.0)) / (duration * 2.0),
+		};
+		progress as f32
+	}
+
+	pub fn finished(&self) -> bool{
+		match self.mode{
+			PlaybackMode::Once | PlaybackMode::Reverse => self.elapsed >= self.duration(),
+			PlaybackMode::Loop | PlaybackMode::PingPong => false,
+		}
+	}
+
+	pub fn restart(&mut self){
+		self.elapsed = 0.0;
+		self.advance_tracks();
+	}
+
+	pub fn advance(&mut self, ctx: &Context){
+		self.advance_by(time::get_delta_time(ctx));
+	}
+
+	pub fn advance_by(&mut self, duration: Duration){
+		let clip_duration = self.duration();
+		if self.finished() || clip_duration <= 0.0{
+			return;
+		}
+		self.elapsed += duration.as_secs_f64();
+		if let PlaybackMode::Loop | PlaybackMode::PingPong = self.mode{
+			// keep the precision for endless clips
+			self.elapsed %= clip_duration * 2.0;
+		}
+		self.advance_tracks();
+	}
+
+	fn advance_tracks(&mut self){
+		let time = self.time();
+		if let Some(s) = self.position.as_mut(){
+			s.advance_to(time);
+		}
+		if let Some(s) = self.rotation.as_mut(){
+			s.advance_to(time);
+		}
+		if let Some(s) = self.scale.as_mut(){
+			s.advance_to(time);
+		}
+		if let Some(s) = self.color.as_mut(){
+			s.advance_to(time);
+		}
+		for s in self.custom.values_mut(){
+			s.advance_to(time);
+		}
+	}
+
+	pub fn get_position(&self) -> TetraVec2{
+		match self.position.as_ref(){
+			Some(s) => s.now().into(),
+			None => Vec2::zero(),
+		}
+	}
+
+	pub fn get_rotation(&self) -> f32{
+		match self.rotation.as_ref(){
+			Some(s) => s.now(),
+			None => 0.0,
+		}
+	}
+
+	pub fn get_scale(&self) -> TetraVec2{
+		match self.scale.as_ref(){
+			Some(s) => s.now().into(),
+			None => Vec2::one(),
+		}
+	}
+
+	pub fn get_color(&self) -> Color{
+		match self.color.as_ref(){
+			Some(s) => s.now().into(),
+			None => Color::WHITE,
+		}
+	}
+
+	/// value of a custom track, 0.0 if there is no track with this name
+	pub fn get_value(&self, name: &str) -> f32{
+		match self.custom.get(name){
+			Some(s) => s.now(),
+			None => 0.0,
+		}
+	}
+
+	/// applies the tracks on top of the params
+	/// position and rotation are added, scale and color are multiplied
+	pub fn apply<P>(&self, params: P) -> DrawParams
+		where
+			P: Into<DrawParams>,
+	{
+		let mut params = params.into();
+		if self.position.is_some(){
+			params.position += self.get_position();
+		}
+		if self.rotation.is_some(){
+			params.rotation += self.get_rotation();
+		}
+		if self.scale.is_some(){
+			params.scale *= self.get_scale();
+		}
+		if self.color.is_some(){
+			let color = self.get_color();
+			params.color = Color::rgba(
+				params.color.r * color.r,
+				params.color.g * color.g,
+				params.color.b * color.b,
+				params.color.a * color.a,
+			);
+		}
+		params
+	}
+}
+
+impl Default for AnimationClip {
+	fn default() -> AnimationClip {
+		AnimationClip::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use keyframe::functions::Linear;
+
+	fn clip(mode: PlaybackMode) -> AnimationClip{
+		AnimationClip::new()
+			.custom("x", vec![Keyframe::new(0.0, 0.0, Linear), Keyframe::new(10.0, 1.0, Linear)])
+			.mode(mode)
+	}
+
+	fn close(a: f64, b: f64) -> bool{
+		(a - b).abs() < 1e-4
+	}
+
+	#[test]
+	fn once(){
+		let mut clip = clip(PlaybackMode::Once);
+		clip.advance_by(Duration::from_millis(500));
+		assert!(close(clip.get_value("x") as f64, 5.0));
+		clip.advance_by(Duration::from_millis(750));
+		assert!(clip.finished());
+		assert!(close(clip.time(), 1.0));
+		assert!(close(clip.get_value("x") as f64, 10.0));
+		clip.restart();
+		assert!(!clip.finished());
+		assert!(close(clip.get_value("x") as f64, 0.0));
+	}
+
+	#[test]
+	fn reverse(){
+		let mut clip = clip(PlaybackMode::Reverse);
+		clip.advance_by(Duration::from_millis(250));
+		assert!(close(clip.get_value("x") as f64, 7.5));
+		clip.advance_by(Duration::from_secs(1));
+		assert!(clip.finished());
+		assert!(close(clip.get_value("x") as f64, 0.0));
+	}
+
+	#[test]
+	fn loop_and_ping_pong(){
+		let mut looped = clip(PlaybackMode::Loop);
+		looped.advance_by(Duration::from_millis(1250));
+		assert!(!looped.finished());
+		assert!(close(looped.time(), 0.25));
+		assert!(close(looped.progress() as f64, 0.25));
+
+		let mut ping_pong = clip(PlaybackMode::PingPong);
+		ping_pong.advance_by(Duration::from_millis(1250));
+		assert!(close(ping_pong.time(), 0.75));
+		assert!(close(ping_pong.progress() as f64, 0.625));
+		assert!(close(ping_pong.get_value("x") as f64, 7.5));
+		ping_pong.advance_by(Duration::from_millis(1000));
+		assert!(close(ping_pong.time(), 0.25));
+	}
+}
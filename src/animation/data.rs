@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+use keyframe::Keyframe;
+use serde::{Serialize, Deserialize};
+use crate::animation::Tween;
+use crate::animation::clip::{AnimationClip, PlaybackMode};
+use crate::animation::values::{TweenVec2, TweenColor};
+use crate::utils::ron::{self, RonFileError};
+pub use crate::animation::easing::{Easing, UnknownEasing};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyframeData<T> {
+	pub value: T,
+	pub time: f64,
+	#[serde(default)]
+	pub easing: Easing,
+}
+
+impl<T: Clone> KeyframeData<T> {
+	pub fn to_keyframe(&self) -> Keyframe<T>{
+		Keyframe::new_dynamic(self.value.clone(), self.time, self.easing.function())
+	}
+}
+
+fn to_keyframes<T: Clone>(keyframes: &[KeyframeData<T>]) -> Vec<Keyframe<T>>{
+	keyframes.iter().map(|k| k.to_keyframe()).collect()
+}
+
+/// AnimationClip in a ron file, keyframe times in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipData {
+	#[serde(default = "default_mode")]
+	pub mode: PlaybackMode,
+	#[serde(default)]
+	pub position: Option<Vec<KeyframeData<TweenVec2>>>,
+	#[serde(default)]
+	pub rotation: Option<Vec<KeyframeData<f32>>>,
+	#[serde(default)]
+	pub scale: Option<Vec<KeyframeData<TweenVec2>>>,
+	#[serde(default)]
+	pub color: Option<Vec<KeyframeData<TweenColor>>>,
+	#[serde(default)]
+	pub custom: HashMap<String, Vec<KeyframeData<f32>>>,
+}
+
+fn default_mode() -> PlaybackMode{
+	PlaybackMode::Once
+}
+
+impl ClipData {
+	pub fn to_clip(&self) -> AnimationClip{
+		let mut clip = AnimationClip::new().mode(self.mode);
+		if let Some(keyframes) = self.position.as_ref(){
+			clip = clip.position(to_keyframes(keyframes));
+		}
+		if let Some(keyframes) = self.rotation.as_ref(){
+			clip = clip.rotation(to_keyframes(keyframes));
+		}
+		if let Some(keyframes) = self.scale.as_ref(){
+			clip = clip.scale(to_keyframes(keyframes));
+		}
+		if let Some(keyframes) = self.color.as_ref(){
+			clip = clip.color(to_keyframes(keyframes));
+		}
+		for (name, keyframes) in self.custom.iter(){
+			clip = clip.custom(name, to_keyframes(keyframes));
+		}
+		clip
+	}
+}
+
+/// Tween in a ron file, keyframe times between 0 and 1
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TweenData {
+	pub keyframes: Vec<KeyframeData<f32>>,
+	pub duration_ms: u64,
+	#[serde(default)]
+	pub delay_ms: u64,
+	/// number of passes in total, 0 repeats forever
+	#[serde(default = "default_repeat")]
+	pub repeat: u32,
+	#[serde(default)]
+	pub yoyo: bool,
+}
+
+fn default_repeat() -> u32{
+	1
+}
+
+impl TweenData {
+	pub fn to_tween(&self) -> Tween{
+		let mut tween = Tween::new(to_keyframes(&self.keyframes), Duration::from_millis(self.duration_ms))
+			.delay(Duration::from_millis(self.delay_ms));
+		if self.repeat == 0{
+			tween = tween.repeat_forever();
+		}else{
+			tween = tween.repeat(self.repeat);
+		}
+		if self.yoyo{
+			tween = tween.yoyo();
+		}
+		tween
+	}
+}
+
+/// named clips and tweens of an animation file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimationData {
+	#[serde(default)]
+	pub clips: HashMap<String, ClipData>,
+	#[serde(default)]
+	pub tweens: HashMap<String, TweenData>,
+}
+
+#[allow(dead_code)]
+impl AnimationData {
+	pub fn from_ron(data: &str) -> Result<AnimationData, ron::error::SpannedError>{
+		ron::from_str(data)
+	}
+
+	/// load at runtime, call it again to reload the file
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<AnimationData, RonFileError>{
+		ron::load_file(path)
+	}
+
+	pub fn get_clip(&self, name: &str) -> Option<AnimationClip>{
+		self.clips.get(name).map(|c| c.to_clip())
+	}
+
+	pub fn get_tween(&self, name: &str) -> Option<Tween>{
+		self.tweens.get(name).map(|t| t.to_tween())
+	}
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::str::FromStr;
+use keyframe::EasingFunction;
+use keyframe::functions::*;
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize, Deserializer};
+#[cfg(feature = "ron_file")]
+use serde::de::{self, DeserializeSeed, EnumAccess, VariantAccess, Visitor};
+
+// the enum, the names for FromStr and the functions of keyframe (same names) from one list
+macro_rules! easings {
+	($default:ident, $($easing:ident),* $(,)?) => {
+		/// easing functions of keyframe which can be chosen by name, e.g. in ron files
+		#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+		#[cfg_attr(feature = "ron_file", derive(Serialize))]
+		pub enum Easing {
+			#[default]
+			$default,
+			$($easing,)*
+		}
+
+		#[allow(dead_code)]
+		const NAMES: &[&str] = &[stringify!($default), $(stringify!($easing),)*];
+
+		const EASINGS: &[(&str, Easing)] = &[
+			(stringify!($default), Easing::$default),
+			$((stringify!($easing), Easing::$easing),)*
+		];
+
+		impl Easing {
+			pub fn function(self) -> Box<dyn EasingFunction + Send + Sync>{
+				match self{
+					Easing::$default => Box::new($default),
+					$(Easing::$easing => Box::new($easing),)*
+				}
+			}
+		}
+	};
+}
+
+easings!(
+	Linear,
+	Step,
+	Hold,
+	EaseIn,
+	EaseOut,
+	EaseInOut,
+	EaseInQuad,
+	EaseOutQuad,
+	EaseInOutQuad,
+	EaseInCubic,
+	EaseOutCubic,
+	EaseInOutCubic,
+	EaseInQuart,
+	EaseOutQuart,
+	EaseInOutQuart,
+	EaseInQuint,
+	EaseOutQuint,
+	EaseInOutQuint,
+);
+
+impl Easing {
+	/// eased value of x (0.0 - 1.0)
+	pub fn ease(self, x: f32) -> f32{
+		self.function().y(x.clamp(0.0, 1.0) as f64) as f32
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownEasing(pub String);
+
+impl fmt::Display for UnknownEasing {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let names: Vec<&str> = EASINGS.iter().map(|(name, _)| *name).collect();
+		write!(f, "unknown easing \"{}\", possible easings are: {}", self.0, names.join(", "))
+	}
+}
+
+impl std::error::Error for UnknownEasing {}
+
+impl FromStr for Easing {
+	type Err = UnknownEasing;
+
+	fn from_str(name: &str) -> Result<Easing, UnknownEasing> {
+		match EASINGS.iter().find(|(n, _)| *n == name){
+			Some((_, easing)) => Ok(*easing),
+			None => Err(UnknownEasing(name.to_string())),
+		}
+	}
+}
+
+// like the derive, but a misspelled easing lists the possible ones
+#[cfg(feature = "ron_file")]
+impl<'de> Deserialize<'de> for Easing {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Easing, D::Error> {
+		deserializer.deserialize_enum("Easing", NAMES, EasingVisitor)
+	}
+}
+
+#[cfg(feature = "ron_file")]
+struct EasingVisitor;
+
+#[cfg(feature = "ron_file")]
+impl<'de> Visitor<'de> for EasingVisitor {
+	type Value = Easing;
+
+	fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "the name of an easing")
+	}
+
+	fn visit_str<E: de::Error>(self, name: &str) -> Result<Easing, E> {
+		name.parse().map_err(de::Error::custom)
+	}
+
+	fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Easing, A::Error> {
+		let (easing, variant) = data.variant_seed(EasingVisitor)?;
+		variant.unit_variant()?;
+		Ok(easing)
+	}
+}
+
+// the name of the variant
+#[cfg(feature = "ron_file")]
+impl<'de> DeserializeSeed<'de> for EasingVisitor {
+	type Value = Easing;
+
+	fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Easing, D::Error> {
+		deserializer.deserialize_identifier(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn names_match_the_variants(){
+		for (name, easing) in EASINGS{
+			assert_eq!(format!("{:?}", easing), *name);
+			assert_eq!(name.parse::<Easing>(), Ok(*easing));
+		}
+	}
+
+	#[test]
+	fn unknown_easing_lists_the_names(){
+		let error = "EaseInQaud".parse::<Easing>().unwrap_err();
+		assert!(error.to_string().contains("\"EaseInQaud\""));
+		assert!(error.to_string().contains("EaseInQuad"));
+	}
+
+	#[cfg(feature = "ron_file")]
+	#[test]
+	fn ron_round_trip(){
+		let text = ron::to_string(&Easing::EaseOutCubic).unwrap();
+		assert_eq!(text, "EaseOutCubic");
+		assert_eq!(ron::from_str::<Easing>(&text).unwrap(), Easing::EaseOutCubic);
+		let error = ron::from_str::<Easing>("EaseOutCubc").unwrap_err();
+		assert!(error.to_string().contains("possible easings are"));
+		let keyframe = ron::from_str::<crate::animation::data::KeyframeData<f32>>("(value: 1.0, time: 0.0, easing: EaseIn)").unwrap();
+		assert_eq!(keyframe.easing, Easing::EaseIn);
+	}
+}
@@ -0,0 +1,341 @@
+use std::any::Any;
+use std::time::Duration;
+use crate::utils::timer::Timer;
+
+/// Everything which can be put into a Sequence, a Parallel group or the TweenManager
+pub trait Playable {
+	/// advances and returns the time which was not needed to finish
+	fn tick(&mut self, delta: Duration) -> Duration;
+	fn finished(&self) -> bool;
+	fn restart(&mut self);
+	fn as_any(&self) -> &dyn Any;
+	fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// waits, can be used in a Sequence
+pub struct Delay {
+	timer: Timer,
+}
+
+impl Delay {
+	pub fn new(duration: Duration) -> Delay{
+		Delay{
+			timer: Timer::from_duration(duration),
+		}
+	}
+}
+
+impl Playable for Delay {
+	fn tick(&mut self, delta: Duration) -> Duration{
+		self.timer.tick(delta);
+		self.timer.elapsed().checked_sub(self.timer.duration()).unwrap_or_default()
+	}
+
+	fn finished(&self) -> bool{
+		self.timer.finished()
+	}
+
+	fn restart(&mut self){
+		self.timer.restart();
+	}
+
+	fn as_any(&self) -> &dyn Any{
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any{
+		self
+	}
+}
+
+/// plays one after the other
+pub struct Sequence {
+	items: Vec<Box<dyn Playable>>,
+	current: usize,
+	repeat: Option<u32>,
+	passes: u32,
+}
+
+#[allow(dead_code)]
+impl Sequence {
+	pub fn new() -> Sequence{
+		Sequence{
+			items: Vec::new(),
+			current: 0,
+			repeat: Some(1),
+			passes: 0,
+		}
+	}
+
+	pub fn then<P: Playable + 'static>(mut self, item: P) -> Self{
+		self.items.push(Box::new(item));
+		self
+	}
+
+	pub fn delay(self, duration: Duration) -> Self{
+		self.then(Delay::new(duration))
+	}
+
+	/// number of passes in total
+	pub fn repeat(mut self, times: u32) -> Self{
+		self.repeat = Some(times.max(1));
+		self
+	}
+
+	pub fn repeat_forever(mut self) -> Self{
+		self.repeat = None;
+		self
+	}
+
+	/// the item at the index, e.g. get::<Tween<TweenVec2>>(1)
+	pub fn get<T: 'static>(&self, index: usize) -> Option<&T>{
+		self.items.get(index)?.as_any().downcast_ref::<T>()
+	}
+
+	pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T>{
+		self.items.get_mut(index)?.as_any_mut().downcast_mut::<T>()
+	}
+
+	/// index of the item which is playing
+	pub fn current(&self) -> usize{
+		self.current
+	}
+}
+
+impl Default for Sequence {
+	fn default() -> Sequence {
+		Sequence::new()
+	}
+}
+
+impl Playable for Sequence {
+	fn tick(&mut self, delta: Duration) -> Duration{
+		let mut delta = delta;
+		let mut pass_start = delta;
+		loop {
+			if self.finished(){
+				return delta;
+			}
+			let item = &mut self.items[self.current];
+			delta = item.tick(delta);
+			if !item.finished(){
+				return Duration::from_secs(0);
+			}
+			self.current += 1;
+			if self.current >= self.items.len(){
+				self.passes += 1;
+				if self.finished(){
+					return delta;
+				}
+				self.current = 0;
+				for item in self.items.iter_mut(){
+					item.restart();
+				}
+				// stop if the whole pass did not need any time
+				if delta == Duration::from_secs(0) || delta >= pass_start{
+					return Duration::from_secs(0);
+				}
+				pass_start = delta;
+			}
+		}
+	}
+
+	fn finished(&self) -> bool{
+		match self.repeat{
+			Some(times) => self.items.is_empty() || self.passes >= times,
+			None => self.items.is_empty(),
+		}
+	}
+
+	fn restart(&mut self){
+		self.current = 0;
+		self.passes = 0;
+		for item in self.items.iter_mut(){
+			item.restart();
+		}
+	}
+
+	fn as_any(&self) -> &dyn Any{
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any{
+		self
+	}
+}
+
+/// plays all at the same time, finished when all are finished
+pub struct Parallel {
+	items: Vec<Box<dyn Playable>>,
+	repeat: Option<u32>,
+	passes: u32,
+}
+
+#[allow(dead_code)]
+impl Parallel {
+	pub fn new() -> Parallel{
+		Parallel{
+			items: Vec::new(),
+			repeat: Some(1),
+			passes: 0,
+		}
+	}
+
+	pub fn with<P: Playable + 'static>(mut self, item: P) -> Self{
+		self.items.push(Box::new(item));
+		self
+	}
+
+	/// number of passes in total
+	pub fn repeat(mut self, times: u32) -> Self{
+		self.repeat = Some(times.max(1));
+		self
+	}
+
+	pub fn repeat_forever(mut self) -> Self{
+		self.repeat = None;
+		self
+	}
+
+	pub fn get<T: 'static>(&self, index: usize) -> Option<&T>{
+		self.items.get(index)?.as_any().downcast_ref::<T>()
+	}
+
+	pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T>{
+		self.items.get_mut(index)?.as_any_mut().downcast_mut::<T>()
+	}
+}
+
+impl Default for Parallel {
+	fn default() -> Parallel {
+		Parallel::new()
+	}
+}
+
+impl Playable for Parallel {
+	fn tick(&mut self, delta: Duration) -> Duration{
+		let mut delta = delta;
+		loop {
+			if self.finished(){
+				return delta;
+			}
+			// the smallest rest is the time after the last item has finished
+			let mut leftover = delta;
+			for item in self.items.iter_mut(){
+				if !item.finished(){
+					leftover = leftover.min(item.tick(delta));
+				}
+			}
+			if !self.items.iter().all(|i| i.finished()){
+				return Duration::from_secs(0);
+			}
+			self.passes += 1;
+			if self.finished(){
+				return leftover;
+			}
+			for item in self.items.iter_mut(){
+				item.restart();
+			}
+			// stop if the whole pass did not need any time
+			if leftover == Duration::from_secs(0) || leftover >= delta{
+				return Duration::from_secs(0);
+			}
+			delta = leftover;
+		}
+	}
+
+	fn finished(&self) -> bool{
+		match self.repeat{
+			Some(times) => self.items.is_empty() || self.passes >= times,
+			None => self.items.is_empty(),
+		}
+	}
+
+	fn restart(&mut self){
+		self.passes = 0;
+		for item in self.items.iter_mut(){
+			item.restart();
+		}
+	}
+
+	fn as_any(&self) -> &dyn Any{
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any{
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::animation::{Tween, Keyframe, Linear};
+
+	fn ms(millis: u64) -> Duration{
+		Duration::from_millis(millis)
+	}
+
+	#[test]
+	fn sequence_passes_the_leftover_time(){
+		let mut sequence = Sequence::new().delay(ms(10)).delay(ms(20));
+		assert_eq!(sequence.tick(ms(15)), ms(0));
+		assert_eq!(sequence.current(), 1);
+		assert_eq!(sequence.tick(ms(20)), ms(5));
+		assert!(sequence.finished());
+		assert_eq!(sequence.tick(ms(7)), ms(7));
+	}
+
+	#[test]
+	fn sequence_repeat(){
+		let mut sequence = Sequence::new().delay(ms(10)).repeat(3);
+		assert_eq!(sequence.tick(ms(25)), ms(0));
+		assert!(!sequence.finished());
+		assert_eq!(sequence.tick(ms(10)), ms(5));
+		assert!(sequence.finished());
+		sequence.restart();
+		assert_eq!(sequence.tick(ms(35)), ms(5));
+	}
+
+	#[test]
+	fn yoyo_tween_in_a_sequence(){
+		let tween = Tween::new(vec![Keyframe::new(0.0f32, 0.0, Linear), Keyframe::new(10.0, 1.0, Linear)], ms(100)).yoyo().repeat(2);
+		let mut sequence = Sequence::new().then(tween).delay(ms(100));
+		sequence.tick(ms(50));
+		assert!((sequence.get::<Tween<f32>>(0).unwrap().value() - 5.0).abs() < 1e-3);
+		sequence.tick(ms(100));
+		assert!((sequence.get::<Tween<f32>>(0).unwrap().value() - 5.0).abs() < 1e-3);
+		// two passes forward and backward take 400ms, the rest is used by the delay
+		assert_eq!(sequence.tick(ms(300)), ms(0));
+		assert_eq!(sequence.current(), 1);
+		assert!(sequence.get::<Tween<f32>>(0).unwrap().value().abs() < 1e-3);
+		assert_eq!(sequence.tick(ms(60)), ms(10));
+	}
+
+	#[test]
+	fn parallel_passes_the_leftover_time(){
+		let mut parallel = Parallel::new().with(Delay::new(ms(10))).with(Delay::new(ms(30)));
+		assert_eq!(parallel.tick(ms(20)), ms(0));
+		assert!(parallel.get::<Delay>(0).unwrap().finished());
+		assert_eq!(parallel.tick(ms(20)), ms(10));
+		assert!(parallel.finished());
+	}
+
+	#[test]
+	fn parallel_repeat(){
+		let mut parallel = Parallel::new().with(Delay::new(ms(10))).with(Delay::new(ms(20))).repeat(3);
+		assert_eq!(parallel.tick(ms(25)), ms(0));
+		assert!(!parallel.finished());
+		assert_eq!(parallel.tick(ms(40)), ms(5));
+		assert!(parallel.finished());
+	}
+
+	#[test]
+	fn endless_groups_without_duration_stop(){
+		let mut parallel = Parallel::new().with(Delay::new(ms(0))).repeat_forever();
+		assert_eq!(parallel.tick(ms(10)), ms(0));
+		let mut sequence = Sequence::new().delay(ms(0)).repeat_forever();
+		assert_eq!(sequence.tick(ms(10)), ms(0));
+		assert!(!parallel.finished() && !sequence.finished());
+	}
+}
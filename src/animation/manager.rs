@@ -0,0 +1,175 @@
+use std::time::Duration;
+use tetra::{Context, time};
+use crate::animation::group::Playable;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TweenId(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TweenEvent {
+	Finished(TweenId),
+}
+
+struct ManagedTween {
+	id: TweenId,
+	playable: Box<dyn Playable>,
+	on_finish: Option<Box<dyn FnMut()>>,
+	finished: bool,
+}
+
+/// updates all added tweens, sequences and groups with the frame delta
+pub struct TweenManager {
+	tweens: Vec<ManagedTween>,
+	events: Vec<TweenEvent>,
+	next_id: usize,
+}
+
+#[allow(dead_code)]
+impl TweenManager {
+	pub fn new() -> TweenManager{
+		TweenManager{
+			tweens: Vec::new(),
+			events: Vec::new(),
+			next_id: 0,
+		}
+	}
+
+	pub fn add<P: Playable + 'static>(&mut self, playable: P) -> TweenId{
+		let id = TweenId(self.next_id);
+		self.next_id += 1;
+		self.tweens.push(ManagedTween{
+			id,
+			// checked in tick, so a tween which is already finished still sends its event
+			finished: false,
+			playable: Box::new(playable),
+			on_finish: None,
+		});
+		id
+	}
+
+	/// the callback is called once when the tween is finished
+	pub fn on_finish<F: FnMut() + 'static>(&mut self, id: TweenId, callback: F){
+		if let Some(tween) = self.tweens.iter_mut().find(|t| t.id == id){
+			tween.on_finish = Some(Box::new(callback));
+		}
+	}
+
+	/// e.g. get::<Tween<TweenColor>>(id)
+	pub fn get<T: 'static>(&self, id: TweenId) -> Option<&T>{
+		self.tweens.iter().find(|t| t.id == id)?.playable.as_any().downcast_ref::<T>()
+	}
+
+	pub fn get_mut<T: 'static>(&mut self, id: TweenId) -> Option<&mut T>{
+		self.tweens.iter_mut().find(|t| t.id == id)?.playable.as_any_mut().downcast_mut::<T>()
+	}
+
+	/// finished tweens are kept (with their last value) until they are removed
+	pub fn is_finished(&self, id: TweenId) -> bool{
+		match self.tweens.iter().find(|t| t.id == id){
+			Some(tween) => tween.finished,
+			None => true,
+		}
+	}
+
+	pub fn restart(&mut self, id: TweenId){
+		if let Some(tween) = self.tweens.iter_mut().find(|t| t.id == id){
+			tween.playable.restart();
+			tween.finished = false;
+		}
+	}
+
+	pub fn remove(&mut self, id: TweenId){
+		self.tweens.retain(|t| t.id != id);
+	}
+
+	pub fn remove_finished(&mut self){
+		self.tweens.retain(|t| !t.finished);
+	}
+
+	pub fn clear(&mut self){
+		self.tweens.clear();
+	}
+
+	/// number of tweens which are not finished
+	pub fn active(&self) -> usize{
+		self.tweens.iter().filter(|t| !t.finished).count()
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	pub fn tick(&mut self, delta: Duration){
+		for tween in self.tweens.iter_mut().filter(|t| !t.finished){
+			tween.playable.tick(delta);
+			if tween.playable.finished(){
+				tween.finished = true;
+				self.events.push(TweenEvent::Finished(tween.id));
+				if let Some(callback) = tween.on_finish.as_mut(){
+					callback();
+				}
+			}
+		}
+	}
+
+	/// events since the last call
+	pub fn events(&mut self) -> Vec<TweenEvent>{
+		std::mem::take(&mut self.events)
+	}
+}
+
+impl Default for TweenManager {
+	fn default() -> TweenManager {
+		TweenManager::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::cell::Cell;
+	use std::rc::Rc;
+	use crate::animation::group::Delay;
+
+	fn ms(millis: u64) -> Duration{
+		Duration::from_millis(millis)
+	}
+
+	#[test]
+	fn finished_events_and_callbacks(){
+		let mut manager = TweenManager::new();
+		let short = manager.add(Delay::new(ms(10)));
+		let long = manager.add(Delay::new(ms(30)));
+		let calls = Rc::new(Cell::new(0));
+		let counter = calls.clone();
+		manager.on_finish(short, move || counter.set(counter.get() + 1));
+
+		manager.tick(ms(20));
+		assert_eq!(manager.events(), vec![TweenEvent::Finished(short)]);
+		assert!(manager.events().is_empty());
+		assert_eq!(manager.active(), 1);
+		manager.tick(ms(20));
+		assert_eq!(manager.events(), vec![TweenEvent::Finished(long)]);
+		// a finished tween sends its event and calls the callback only once
+		manager.tick(ms(20));
+		assert!(manager.events().is_empty());
+		assert_eq!(calls.get(), 1);
+		assert!(manager.is_finished(short) && manager.is_finished(long));
+	}
+
+	#[test]
+	fn restart_and_remove(){
+		let mut manager = TweenManager::new();
+		let id = manager.add(Delay::new(ms(0)));
+		assert!(!manager.is_finished(id));
+		manager.tick(ms(16));
+		assert_eq!(manager.events(), vec![TweenEvent::Finished(id)]);
+		manager.restart(id);
+		assert_eq!(manager.active(), 1);
+		manager.tick(ms(16));
+		assert_eq!(manager.events(), vec![TweenEvent::Finished(id)]);
+		manager.remove_finished();
+		assert!(manager.get::<Delay>(id).is_none());
+		assert!(manager.is_finished(id));
+	}
+}
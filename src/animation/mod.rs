@@ -3,54 +3,195 @@ pub use keyframe::*;
 pub use keyframe::functions::*;
 pub use keyframe_derive::*;
 
+pub mod clip;
+#[cfg(feature = "ron_file")]
+pub mod data;
+pub mod easing;
+pub mod group;
+pub mod manager;
+pub mod state_machine;
+pub mod values;
+
 use crate::utils::timer::Timer;
+use crate::animation::group::Playable;
+use std::any::Any;
 use std::time::Duration;
+use tetra::{Context, time};
 
-pub struct Tween {
+/// Tween over keyframes with times between 0 and 1, the duration is set by the tween
+pub struct Tween<T = f32> {
 	timer: Timer,
-	sequence: Option<AnimationSequence<f32>>,
-	repeat: bool,
+	delay: Timer,
+	sequence: AnimationSequence<T>,
+	repeat: Option<u32>,
+	passes: u32,
+	yoyo: bool,
+	backwards: bool,
+	paused: bool,
+	finished: bool,
 }
 
-impl Tween{
-	pub fn from_keyframes(keyframes: Vec<Keyframe<f32>>,start_at: u64, duration_sec: u64, repeat: bool) -> Tween{
-		let sequence = AnimationSequence::from(keyframes);
-		let mut timer = Timer::new_sec(duration_sec);
-		timer.advance_by(Duration::from_secs(start_at));
-		Self{
-			timer,
-			sequence: Some(sequence),
-			repeat,
+#[allow(dead_code)]
+impl<T> Tween<T>
+	where
+		T: CanTween + Clone + Default,
+{
+	/// plays once, use repeat(), yoyo() and delay() to change it
+	pub fn new(keyframes: Vec<Keyframe<T>>, duration: Duration) -> Tween<T>{
+		let mut tween = Tween{
+			timer: Timer::from_duration(duration),
+			delay: Timer::new(0),
+			sequence: AnimationSequence::from(keyframes),
+			repeat: Some(1),
+			passes: 0,
+			yoyo: false,
+			backwards: false,
+			paused: false,
+			finished: false,
+		};
+		tween.update_sequence();
+		tween
+	}
+
+	pub fn from_keyframes(keyframes: Vec<Keyframe<T>>,start_at: u64, duration_sec: u64, repeat: bool) -> Tween<T>{
+		let mut tween = Tween::new(keyframes, Duration::from_secs(duration_sec));
+		if repeat{
+			tween.repeat = None;
 		}
+		tween.tick(Duration::from_secs(start_at));
+		tween
+	}
+
+	/// waits before the first pass
+	pub fn delay(mut self, delay: Duration) -> Self{
+		self.delay = Timer::from_duration(delay);
+		self
+	}
+
+	/// number of passes in total
+	pub fn repeat(mut self, times: u32) -> Self{
+		self.repeat = Some(times.max(1));
+		self
+	}
+
+	pub fn repeat_forever(mut self) -> Self{
+		self.repeat = None;
+		self
+	}
+
+	/// a pass is played forward and then backward
+	pub fn yoyo(mut self) -> Self{
+		self.yoyo = true;
+		self
 	}
 
 	pub fn restart(&mut self){
 		self.timer.restart();
+		self.delay.restart();
+		self.passes = 0;
+		self.backwards = false;
+		self.finished = false;
+		self.update_sequence();
 	}
 
 	pub fn finished(&self) -> bool{
-		if let Some(s) = self.sequence.as_ref(){
-			s.finished()
-		}else{
-			false
-		}
+		self.finished
 	}
 
-	pub fn update(&mut self){
-		if let Some(s) = self.sequence.as_mut() {
-			s.advance_to(self.timer.value() as f64);
-		}
+	pub fn pause(&mut self){
+		self.paused = true;
+	}
 
-		if self.timer.finished() && self.repeat{
+	pub fn resume(&mut self){
+		self.paused = false;
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	/// returns the time which was not needed to finish the tween
+	pub fn tick(&mut self, delta: Duration) -> Duration{
+		if self.finished{
+			return delta;
+		}
+		if self.paused{
+			return Duration::from_secs(0);
+		}
+		let mut delta = delta;
+		if !self.delay.finished(){
+			self.delay.tick(delta);
+			if !self.delay.finished(){
+				return Duration::from_secs(0);
+			}
+			delta = self.delay.elapsed() - self.delay.duration();
+		}
+		self.timer.tick(delta);
+		let mut leftover = Duration::from_secs(0);
+		while self.timer.finished(){
+			let rest = self.timer.elapsed() - self.timer.duration();
+			if self.yoyo && !self.backwards{
+				self.backwards = true;
+			}else{
+				self.passes += 1;
+				if let Some(times) = self.repeat{
+					if self.passes >= times{
+						self.finished = true;
+						leftover = rest;
+						break;
+					}
+				}
+				self.backwards = false;
+			}
 			self.timer.restart();
+			if self.timer.duration() == Duration::from_secs(0){
+				break;
+			}
+			self.timer.advance_by(rest);
 		}
+		self.update_sequence();
+		leftover
 	}
 
-	pub fn value(&self) -> f32{
-		if let Some(s) = self.sequence.as_ref(){
-			s.now()
-		}else{
+	fn update_sequence(&mut self){
+		let progress = if self.finished{
 			1.0
+		}else{
+			self.timer.value()
+		};
+		if self.backwards{
+			self.sequence.advance_to(1.0 - progress as f64);
+		}else{
+			self.sequence.advance_to(progress as f64);
 		}
 	}
-}
\ No newline at end of file
+
+	pub fn value(&self) -> T{
+		self.sequence.now()
+	}
+}
+
+impl<T> Playable for Tween<T>
+	where
+		T: CanTween + Clone + Default + 'static,
+{
+	fn tick(&mut self, delta: Duration) -> Duration{
+		Tween::tick(self, delta)
+	}
+
+	fn finished(&self) -> bool{
+		self.finished
+	}
+
+	fn restart(&mut self){
+		Tween::restart(self);
+	}
+
+	fn as_any(&self) -> &dyn Any{
+		self
+	}
+
+	fn as_any_mut(&mut self) -> &mut dyn Any{
+		self
+	}
+}
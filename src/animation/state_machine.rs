@@ -0,0 +1,390 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use log::{debug, error};
+use tetra::graphics::{Color, DrawParams};
+use tetra::{Context, time};
+use crate::animation::clip::AnimationClip;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Parameter {
+	Bool(bool),
+	Float(f32),
+	/// is reset as soon as a transition has used it
+	Trigger(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+	IsTrue(String),
+	IsFalse(String),
+	Greater(String, f32),
+	Less(String, f32),
+	Trigger(String),
+}
+
+/// transition between two states, without a from state it is valid from any state
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateTransition {
+	from: Option<String>,
+	to: String,
+	conditions: Vec<Condition>,
+	exit_time: Option<f32>,
+	fade: Duration,
+}
+
+#[allow(dead_code)]
+impl StateTransition {
+	pub fn new(from: &str, to: &str) -> StateTransition{
+		StateTransition{
+			from: Some(from.to_string()),
+			to: to.to_string(),
+			conditions: Vec::new(),
+			exit_time: None,
+			fade: Duration::from_secs(0),
+		}
+	}
+
+	/// transition from any state
+	pub fn any(to: &str) -> StateTransition{
+		StateTransition{
+			from: None,
+			..StateTransition::new("", to)
+		}
+	}
+
+	/// all conditions must be met
+	pub fn when(mut self, condition: Condition) -> Self{
+		self.conditions.push(condition);
+		self
+	}
+
+	/// the clip of the current state must have reached this progress (0.0 - 1.0)
+	pub fn exit_time(mut self, exit_time: f32) -> Self{
+		self.exit_time = Some(exit_time);
+		self
+	}
+
+	/// cross-fade duration between the two clips
+	pub fn fade(mut self, fade: Duration) -> Self{
+		self.fade = fade;
+		self
+	}
+}
+
+/// switches between AnimationClips depending on parameters
+pub struct AnimationStateMachine {
+	states: HashMap<String, AnimationClip>,
+	transitions: Vec<StateTransition>,
+	parameters: HashMap<String, Parameter>,
+	current: String,
+	previous: Option<String>,
+	fade: Duration,
+	fade_elapsed: Duration,
+	state_time: Duration,
+}
+
+#[allow(dead_code)]
+impl AnimationStateMachine {
+	pub fn new(initial_state: &str, clip: AnimationClip) -> AnimationStateMachine{
+		let mut states = HashMap::new();
+		states.insert(initial_state.to_string(), clip);
+		AnimationStateMachine{
+			states,
+			transitions: Vec::new(),
+			parameters: HashMap::new(),
+			current: initial_state.to_string(),
+			previous: None,
+			fade: Duration::from_secs(0),
+			fade_elapsed: Duration::from_secs(0),
+			state_time: Duration::from_secs(0),
+		}
+	}
+
+	pub fn state(mut self, name: &str, clip: AnimationClip) -> Self{
+		self.states.insert(name.to_string(), clip);
+		self
+	}
+
+	pub fn transition(mut self, transition: StateTransition) -> Self{
+		if !self.states.contains_key(&transition.to){
+			error!("transition to unknown state {}!", transition.to);
+		}
+		self.transitions.push(transition);
+		self
+	}
+
+	pub fn set_bool(&mut self, name: &str, value: bool){
+		self.parameters.insert(name.to_string(), Parameter::Bool(value));
+	}
+
+	pub fn set_float(&mut self, name: &str, value: f32){
+		self.parameters.insert(name.to_string(), Parameter::Float(value));
+	}
+
+	pub fn set_trigger(&mut self, name: &str){
+		self.parameters.insert(name.to_string(), Parameter::Trigger(true));
+	}
+
+	pub fn reset_trigger(&mut self, name: &str){
+		self.parameters.insert(name.to_string(), Parameter::Trigger(false));
+	}
+
+	pub fn get_parameter(&self, name: &str) -> Option<Parameter>{
+		self.parameters.get(name).copied()
+	}
+
+	pub fn get_parameters(&self) -> &HashMap<String, Parameter>{
+		&self.parameters
+	}
+
+	pub fn current_state(&self) -> &str{
+		&self.current
+	}
+
+	/// the state which is faded out
+	pub fn previous_state(&self) -> Option<&str>{
+		self.previous.as_deref()
+	}
+
+	/// time since the current state was entered
+	pub fn state_time(&self) -> Duration{
+		self.state_time
+	}
+
+	pub fn is_fading(&self) -> bool{
+		self.previous.is_some()
+	}
+
+	/// weight of the current state, 1.0 if there is no cross-fade
+	pub fn fade_progress(&self) -> f32{
+		if self.previous.is_none() || self.fade.as_secs_f32() <= 0.0{
+			1.0
+		}else{
+			(self.fade_elapsed.as_secs_f32() / self.fade.as_secs_f32()).min(1.0)
+		}
+	}
+
+	pub fn get_clip(&self) -> &AnimationClip{
+		&self.states[&self.current]
+	}
+
+	/// change the state immediately, without transition
+	pub fn force_state(&mut self, name: &str){
+		if self.states.contains_key(name){
+			self.enter(name, Duration::from_secs(0));
+		}else{
+			error!("state {} not found!", name);
+		}
+	}
+
+	pub fn advance(&mut self, ctx: &Context){
+		self.advance_by(time::get_delta_time(ctx));
+	}
+
+	pub fn advance_by(&mut self, duration: Duration){
+		self.state_time += duration;
+		if let Some(clip) = self.states.get_mut(&self.current){
+			clip.advance_by(duration);
+		}
+		if let Some(previous) = self.previous.clone(){
+			self.fade_elapsed += duration;
+			if self.fade_elapsed >= self.fade{
+				self.previous = None;
+			}else if let Some(clip) = self.states.get_mut(&previous){
+				clip.advance_by(duration);
+			}
+		}
+		if let Some(index) = self.find_transition(){
+			let transition = self.transitions[index].clone();
+			self.consume_triggers(&transition.conditions);
+			debug!("animation state {} -> {}", self.current, transition.to);
+			self.enter(&transition.to, transition.fade);
+		}
+	}
+
+	// transitions from any state have priority
+	fn find_transition(&self) -> Option<usize>{
+		let any_state = self.transitions.iter().position(|t| t.from.is_none() && t.to != self.current && self.is_possible(t));
+		any_state.or_else(|| self.transitions.iter().position(|t| t.from.as_deref() == Some(self.current.as_str()) && self.is_possible(t)))
+	}
+
+	fn is_possible(&self, transition: &StateTransition) -> bool{
+		if !self.states.contains_key(&transition.to){
+			return false;
+		}
+		if let Some(exit_time) = transition.exit_time{
+			let clip = &self.states[&self.current];
+			if !clip.finished() && clip.progress() < exit_time{
+				return false;
+			}
+		}
+		transition.conditions.iter().all(|c| self.is_met(c))
+	}
+
+	fn is_met(&self, condition: &Condition) -> bool{
+		match condition{
+			Condition::IsTrue(name) => self.parameters.get(name) == Some(&Parameter::Bool(true)),
+			Condition::IsFalse(name) => self.parameters.get(name) != Some(&Parameter::Bool(true)),
+			Condition::Greater(name, value) => match self.parameters.get(name){
+				Some(Parameter::Float(v)) => v > value,
+				_ => false,
+			},
+			Condition::Less(name, value) => match self.parameters.get(name){
+				Some(Parameter::Float(v)) => v < value,
+				_ => false,
+			},
+			Condition::Trigger(name) => self.parameters.get(name) == Some(&Parameter::Trigger(true)),
+		}
+	}
+
+	fn consume_triggers(&mut self, conditions: &[Condition]){
+		for condition in conditions{
+			if let Condition::Trigger(name) = condition{
+				self.reset_trigger(name);
+			}
+		}
+	}
+
+	fn enter(&mut self, name: &str, fade: Duration){
+		if fade > Duration::from_secs(0) && name != self.current{
+			self.previous = Some(self.current.clone());
+		}else{
+			self.previous = None;
+		}
+		self.current = name.to_string();
+		self.fade = fade;
+		self.fade_elapsed = Duration::from_secs(0);
+		self.state_time = Duration::from_secs(0);
+		if let Some(clip) = self.states.get_mut(name){
+			clip.restart();
+		}
+	}
+
+	/// value of a custom track, blended during a cross-fade
+	pub fn get_value(&self, name: &str) -> f32{
+		let value = self.states[&self.current].get_value(name);
+		match self.previous.as_ref(){
+			Some(previous) => lerp(self.states[previous].get_value(name), value, self.fade_progress()),
+			None => value,
+		}
+	}
+
+	/// applies the clip of the current state, blended during a cross-fade
+	pub fn apply<P>(&self, params: P) -> DrawParams
+		where
+			P: Into<DrawParams>,
+	{
+		let params = params.into();
+		let current = self.states[&self.current].apply(params.clone());
+		match self.previous.as_ref(){
+			Some(previous) => {
+				let previous = self.states[previous].apply(params);
+				let weight = self.fade_progress();
+				DrawParams{
+					position: previous.position + (current.position - previous.position) * weight,
+					rotation: lerp(previous.rotation, current.rotation, weight),
+					scale: previous.scale + (current.scale - previous.scale) * weight,
+					color: Color::rgba(
+						lerp(previous.color.r, current.color.r, weight),
+						lerp(previous.color.g, current.color.g, weight),
+						lerp(previous.color.b, current.color.b, weight),
+						lerp(previous.color.a, current.color.a, weight),
+					),
+					..current
+				}
+			}
+			None => current,
+		}
+	}
+}
+
+fn lerp(from: f32, to: f32, weight: f32) -> f32{
+	from + (to - from) * weight
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::animation::clip::PlaybackMode;
+	use keyframe::Keyframe;
+	use keyframe::functions::Linear;
+
+	fn ms(ms: u64) -> Duration{
+		Duration::from_millis(ms)
+	}
+
+	// one second with a constant custom value
+	fn clip(value: f32) -> AnimationClip{
+		AnimationClip::new().custom("value", vec![Keyframe::new(value, 0.0, Linear), Keyframe::new(value, 1.0, Linear)])
+	}
+
+	fn machine() -> AnimationStateMachine{
+		AnimationStateMachine::new("idle", clip(0.0).mode(PlaybackMode::Loop))
+			.state("run", clip(10.0).mode(PlaybackMode::Loop))
+			.state("hit", clip(20.0))
+			.transition(StateTransition::new("idle", "run").when(Condition::Greater("speed".to_string(), 0.5)).fade(ms(200)))
+			.transition(StateTransition::new("run", "idle").when(Condition::Less("speed".to_string(), 0.5)))
+			.transition(StateTransition::new("hit", "idle").exit_time(1.0))
+			.transition(StateTransition::any("hit").when(Condition::Trigger("hit".to_string())))
+	}
+
+	#[test]
+	fn float_conditions(){
+		let mut machine = machine();
+		machine.advance_by(ms(100));
+		assert_eq!(machine.current_state(), "idle");
+		machine.set_float("speed", 1.0);
+		machine.advance_by(ms(16));
+		assert_eq!(machine.current_state(), "run");
+		machine.set_float("speed", 0.0);
+		machine.advance_by(ms(16));
+		assert_eq!(machine.current_state(), "idle");
+		assert!(!machine.is_fading());
+	}
+
+	#[test]
+	fn cross_fade_weights(){
+		let mut machine = machine();
+		machine.set_float("speed", 1.0);
+		machine.advance_by(ms(16));
+		assert_eq!(machine.previous_state(), Some("idle"));
+		assert_eq!(machine.fade_progress(), 0.0);
+		machine.advance_by(ms(50));
+		assert!((machine.fade_progress() - 0.25).abs() < 1e-4);
+		assert!((machine.get_value("value") - 2.5).abs() < 1e-3);
+		machine.advance_by(ms(100));
+		assert!((machine.get_value("value") - 7.5).abs() < 1e-3);
+		machine.advance_by(ms(50));
+		assert!(!machine.is_fading());
+		assert_eq!(machine.fade_progress(), 1.0);
+		assert!((machine.get_value("value") - 10.0).abs() < 1e-3);
+	}
+
+	#[test]
+	fn triggers_and_exit_time(){
+		let mut machine = machine();
+		machine.set_trigger("hit");
+		machine.advance_by(ms(16));
+		assert_eq!(machine.current_state(), "hit");
+		assert_eq!(machine.get_parameter("hit"), Some(Parameter::Trigger(false)));
+		machine.advance_by(ms(900));
+		assert_eq!(machine.current_state(), "hit");
+		machine.advance_by(ms(100));
+		assert_eq!(machine.current_state(), "idle");
+	}
+
+	#[test]
+	fn bool_conditions(){
+		let mut machine = AnimationStateMachine::new("ground", clip(0.0))
+			.state("air", clip(1.0))
+			.transition(StateTransition::new("ground", "air").when(Condition::IsFalse("grounded".to_string())).when(Condition::IsTrue("jumping".to_string())));
+		machine.set_bool("jumping", true);
+		machine.set_bool("grounded", true);
+		machine.advance_by(ms(16));
+		assert_eq!(machine.current_state(), "ground");
+		machine.set_bool("grounded", false);
+		machine.advance_by(ms(16));
+		assert_eq!(machine.current_state(), "air");
+		assert_eq!(machine.state_time(), Duration::from_secs(0));
+	}
+}
@@ -0,0 +1,87 @@
+use keyframe_derive::CanTween;
+use tetra::graphics::{Color, Rectangle};
+use tetra::math::Vec2;
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize};
+
+/// Vec2 which can be used in keyframes
+#[derive(CanTween, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub struct TweenVec2 {
+	pub x: f32,
+	pub y: f32,
+}
+
+impl TweenVec2 {
+	pub fn new(x: f32, y: f32) -> TweenVec2{
+		TweenVec2{ x, y }
+	}
+}
+
+impl From<Vec2<f32>> for TweenVec2 {
+	fn from(vec: Vec2<f32>) -> TweenVec2 {
+		TweenVec2::new(vec.x, vec.y)
+	}
+}
+
+impl From<TweenVec2> for Vec2<f32> {
+	fn from(vec: TweenVec2) -> Vec2<f32> {
+		Vec2::new(vec.x, vec.y)
+	}
+}
+
+/// Color which can be used in keyframes
+#[derive(CanTween, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub struct TweenColor {
+	pub r: f32,
+	pub g: f32,
+	pub b: f32,
+	pub a: f32,
+}
+
+impl TweenColor {
+	pub fn rgba(r: f32, g: f32, b: f32, a: f32) -> TweenColor{
+		TweenColor{ r, g, b, a }
+	}
+}
+
+impl From<Color> for TweenColor {
+	fn from(color: Color) -> TweenColor {
+		TweenColor::rgba(color.r, color.g, color.b, color.a)
+	}
+}
+
+impl From<TweenColor> for Color {
+	fn from(color: TweenColor) -> Color {
+		Color::rgba(color.r, color.g, color.b, color.a)
+	}
+}
+
+/// Rectangle which can be used in keyframes
+#[derive(CanTween, Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub struct TweenRectangle {
+	pub x: f32,
+	pub y: f32,
+	pub width: f32,
+	pub height: f32,
+}
+
+impl TweenRectangle {
+	pub fn new(x: f32, y: f32, width: f32, height: f32) -> TweenRectangle{
+		TweenRectangle{ x, y, width, height }
+	}
+}
+
+impl From<Rectangle> for TweenRectangle {
+	fn from(rectangle: Rectangle) -> TweenRectangle {
+		TweenRectangle::new(rectangle.x, rectangle.y, rectangle.width, rectangle.height)
+	}
+}
+
+impl From<TweenRectangle> for Rectangle {
+	fn from(rectangle: TweenRectangle) -> Rectangle {
+		Rectangle::new(rectangle.x, rectangle.y, rectangle.width, rectangle.height)
+	}
+}
@@ -3,6 +3,10 @@ use tetra::math::Vec2;
 use crate::TetraVec2;
 use serde::{Serialize,Deserialize};
 use log::{error};
+use std::path::Path;
+use crate::utils::ron::{self, RonFileError};
+#[cfg(feature = "sound")]
+use crate::sound::mixer::{Mixer, MixerSettings, Bus};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
@@ -22,6 +26,9 @@ pub struct Config {
 	pub quit_on_escape: bool,
 	// Game Music
 	pub master_volume: f32,
+	#[cfg(feature = "sound")]
+	#[serde(default)]
+	pub mixer: MixerSettings,
 }
 
 impl Config {
@@ -30,6 +37,28 @@ impl Config {
 		env!("CARGO_PKG_VERSION").to_owned()
 	}
 
+	/// load at runtime, call it again to reload the file
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Config, RonFileError>{
+		ron::load_file(path)
+	}
+
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RonFileError>{
+		ron::save_file(path, self)
+	}
+
+	/// mixer with master_volume and the bus volumes of the config
+	#[cfg(feature = "sound")]
+	pub fn create_mixer(&self) -> Mixer{
+		Mixer::from_settings(self.master_volume, self.mixer.clone())
+	}
+
+	/// takes over the volumes of the mixer, call save() to persist them
+	#[cfg(feature = "sound")]
+	pub fn set_mixer(&mut self, mixer: &Mixer){
+		self.master_volume = mixer.get_volume(Bus::Master);
+		self.mixer = mixer.get_settings();
+	}
+
 	pub fn half_window(&self) -> TetraVec2 {
 		Vec2::new((self.window_width / 2) as f32, (self.window_height / 2) as f32)
 	}
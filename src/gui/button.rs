@@ -6,12 +6,15 @@ use tetra::graphics::ui::NineSlice;
 use tetra::math::Vec2;
 use crate::TetraVec2;
 use tetra::graphics::text::{Text, Font};
+use crate::gui::focus::Focusable;
+use crate::gui::layout::Layoutable;
 
 #[allow(dead_code)]
 pub struct Button{
 	pressed: bool,
 	visible: bool,
 	disabled: bool,
+	focused: bool,
 	centered: bool,
 	position: TetraVec2,
 	state: ButtonState,
@@ -35,6 +38,7 @@ impl Button{
 			pressed: false,
 			visible: true,
 			disabled: false,
+			focused: false,
 			centered: true,
 			state: ButtonState::Normal,
 			text: Text::new(text, font),
@@ -110,13 +114,8 @@ impl Button{
 		if self.visible && !self.disabled{
 			let mouse_position = &input::get_mouse_position(ctx).round();
 
-			let text_bounds = self.text.get_bounds(ctx).unwrap();
-			if self.panel.width() < text_bounds.width+self.text_frame_size{
-				self.panel.set_width(text_bounds.width+self.text_frame_size);
-			}
-			if self.panel.height() < text_bounds.height+self.text_frame_size{
-				self.panel.set_height(text_bounds.height+self.text_frame_size);
-			}
+			let size = self.get_panel_size(ctx);
+			self.panel.set_size(size.x, size.y);
 
 			let bounds = Rectangle::new(0.0,0.0, self.panel.width(), self.panel.height());
 
@@ -129,12 +128,23 @@ impl Button{
 				if input::is_mouse_button_released(ctx, MouseButton::Left){
 					self.pressed = true;
 				}
+			}else if self.focused{
+				self.state = ButtonState::Focused;
 			}else{
 				self.state = ButtonState::Normal;
 			}
 			self.panel.set_texture(self.textures[&self.state].clone());
 		}
 	}
+
+	// the panel grows with the text
+	fn get_panel_size(&self, ctx: &mut Context) -> TetraVec2{
+		let text_bounds = self.text.get_bounds(ctx).unwrap();
+		TetraVec2::new(
+			self.panel.width().max(text_bounds.width+self.text_frame_size),
+			self.panel.height().max(text_bounds.height+self.text_frame_size),
+		)
+	}
 }
 
 impl Drawable for Button {
@@ -148,7 +158,8 @@ impl Drawable for Button {
 				position_tmp -= Vec2::new(self.panel.width() / 2.0, self.panel.height() / 2.0).round();
 			}
 
-			self.panel.draw(ctx, position_tmp);
+			let tint = if self.state == ButtonState::Focused { FOCUSED_TINT } else { Color::WHITE };
+			self.panel.draw(ctx, DrawParams::new().position(position_tmp).color(tint));
 			let bounds = self.text.get_bounds(ctx).unwrap();
 			self.text.draw(ctx, DrawParams::new()
 				.color(self.text_colors[&self.state])
@@ -159,6 +170,41 @@ impl Drawable for Button {
 	}
 }
 
+impl Focusable for Button {
+	fn get_bounds(&self, _ctx: &mut Context) -> Rectangle{
+		let mut position = self.position;
+		if self.centered{
+			position -= Vec2::new(self.panel.width() / 2.0, self.panel.height() / 2.0);
+		}
+		Rectangle::new(position.x, position.y, self.panel.width(), self.panel.height())
+	}
+
+	fn set_focused(&mut self, focused: bool){
+		self.focused = focused;
+	}
+
+	fn activate(&mut self){
+		self.pressed = true;
+	}
+
+	fn is_focusable(&self) -> bool{
+		self.visible && !self.disabled
+	}
+}
+
+impl Layoutable for Button {
+	fn get_size(&self, ctx: &mut Context) -> TetraVec2{
+		self.get_panel_size(ctx)
+	}
+
+	fn set_layout_bounds(&mut self, _ctx: &mut Context, bounds: Rectangle){
+		self.position = TetraVec2::new(bounds.x, bounds.y);
+		if self.centered{
+			self.position += TetraVec2::new(bounds.width / 2.0, bounds.height / 2.0).round();
+		}
+	}
+}
+
 fn is_inside_hover_area(centered: bool, draw_position: TetraVec2, area: Rectangle, position: TetraVec2) -> bool{
 	let mut pos_x = draw_position.x;
 	let mut pos_y = draw_position.y;
@@ -173,12 +219,16 @@ fn is_inside_hover_area(centered: bool, draw_position: TetraVec2, area: Rectangl
 	)
 }
 
+// the focused button uses the texture of the hover state, tinted so keyboard and mouse can be told apart
+const FOCUSED_TINT: Color = Color::rgb(0.6, 0.9, 1.0);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum ButtonState {
 	Normal,
 	Hover,
 	Pressed,
 	Disabled,
+	Focused,
 }
 
 fn get_textures(ctx: &mut Context) -> tetra::Result<HashMap<ButtonState, Texture>>{
@@ -187,6 +237,7 @@ fn get_textures(ctx: &mut Context) -> tetra::Result<HashMap<ButtonState, Texture
 	textures.insert(ButtonState::Hover, Texture::from_file_data(ctx, include_bytes!("../../resources/button_1.png"))?);
 	textures.insert(ButtonState::Pressed, Texture::from_file_data(ctx, include_bytes!("../../resources/button_2.png"))?);
 	textures.insert(ButtonState::Disabled, Texture::from_file_data(ctx, include_bytes!("../../resources/button_3.png"))?);
+	textures.insert(ButtonState::Focused, Texture::from_file_data(ctx, include_bytes!("../../resources/button_1.png"))?);
 	Ok(textures)
 }
 
@@ -196,5 +247,6 @@ fn get_text_colors() -> HashMap<ButtonState, Color>{
 	text_colors.insert(ButtonState::Hover, Color::rgb(0.984,0.875,0.42));
 	text_colors.insert(ButtonState::Pressed, Color::rgb(0.075,0.698,0.949));
 	text_colors.insert(ButtonState::Disabled, Color::rgb(0.2,0.2,0.2));
+	text_colors.insert(ButtonState::Focused, Color::rgb(0.42,0.949,0.984));
 	text_colors
 }
\ No newline at end of file
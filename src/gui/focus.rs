@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use tetra::{Context, Event};
+use tetra::graphics::Rectangle;
+use tetra::input::{self, GamepadAxis, GamepadButton, Key};
+use crate::input_action;
+use crate::TetraVec2;
+
+// a stick has to cross this value to move the focus
+const STICK_THRESHOLD: f32 = 0.5;
+
+/// a widget which can be selected with the keyboard or a gamepad
+pub trait Focusable {
+	/// area on the screen, used for the spatial navigation
+	fn get_bounds(&self, ctx: &mut Context) -> Rectangle;
+	fn set_focused(&mut self, focused: bool);
+	/// is called by the confirm action, e.g. the button is pressed
+	fn activate(&mut self);
+	/// hidden or disabled widgets are skipped
+	fn is_focusable(&self) -> bool{
+		true
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FocusDirection {
+	Up,
+	Down,
+	Left,
+	Right,
+}
+
+impl FocusDirection {
+	fn vector(self) -> TetraVec2{
+		match self{
+			FocusDirection::Up => TetraVec2::new(0.0, -1.0),
+			FocusDirection::Down => TetraVec2::new(0.0, 1.0),
+			FocusDirection::Left => TetraVec2::new(-1.0, 0.0),
+			FocusDirection::Right => TetraVec2::new(1.0, 0.0),
+		}
+	}
+}
+
+/// Moves the focus between the widgets of a menu.
+/// The widgets are passed by index in tab order, e.g. focus.event(ctx, &event, &mut [&mut self.start, &mut self.quit]).
+/// Without an explicit neighbour the nearest widget in the direction gets the focus.
+#[derive(Debug, Clone, Default)]
+pub struct FocusManager {
+	focused: Option<usize>,
+	neighbours: HashMap<(usize, FocusDirection), usize>,
+	wrap: bool,
+	stick: (f32, f32),
+}
+
+#[allow(dead_code)]
+impl FocusManager {
+	pub fn new() -> FocusManager{
+		FocusManager{
+			wrap: true,
+			..FocusManager::default()
+		}
+	}
+
+	/// explicit neighbour instead of the spatial navigation
+	pub fn neighbour(mut self, from: usize, direction: FocusDirection, to: usize) -> Self{
+		self.neighbours.insert((from, direction), to);
+		self
+	}
+
+	/// the tab order starts again at the end, default is true
+	pub fn wrap(mut self, wrap: bool) -> Self{
+		self.wrap = wrap;
+		self
+	}
+
+	pub fn get_focused(&self) -> Option<usize>{
+		self.focused
+	}
+
+	pub fn set_focused(&mut self, index: Option<usize>, widgets: &mut [&mut dyn Focusable]){
+		self.focused = index.filter(|i| matches!(widgets.get(*i), Some(widget) if widget.is_focusable()));
+		for (i, widget) in widgets.iter_mut().enumerate(){
+			widget.set_focused(Some(i) == self.focused);
+		}
+	}
+
+	/// next widget in tab order
+	pub fn next(&mut self, widgets: &mut [&mut dyn Focusable]){
+		let next = self.step(widgets, true);
+		self.set_focused(next, widgets);
+	}
+
+	/// previous widget in tab order
+	pub fn previous(&mut self, widgets: &mut [&mut dyn Focusable]){
+		let previous = self.step(widgets, false);
+		self.set_focused(previous, widgets);
+	}
+
+	pub fn move_focus(&mut self, ctx: &mut Context, direction: FocusDirection, widgets: &mut [&mut dyn Focusable]){
+		let current = match self.focused{
+			Some(current) => current,
+			None => return self.next(widgets),
+		};
+		let target = match self.neighbours.get(&(current, direction)){
+			Some(neighbour) => Some(*neighbour),
+			None => {
+				// None for the widgets which are skipped
+				let bounds: Vec<Option<Rectangle>> = widgets.iter().enumerate()
+					.map(|(i, w)| if i == current || w.is_focusable() { Some(w.get_bounds(ctx)) } else { None })
+					.collect();
+				nearest(current, direction, &bounds)
+			}
+		};
+		if target.is_some(){
+			self.set_focused(target, widgets);
+		}
+	}
+
+	/// activates the focused widget and returns its index
+	pub fn activate(&mut self, widgets: &mut [&mut dyn Focusable]) -> Option<usize>{
+		let focused = self.focused?;
+		let widget = widgets.get_mut(focused)?;
+		if !widget.is_focusable(){
+			return None;
+		}
+		widget.activate();
+		Some(focused)
+	}
+
+	/// arrows, tab, the d-pad and the left stick move the focus, the confirmation activates the focused widget,
+	/// returns the index of the activated widget
+	pub fn event(&mut self, ctx: &mut Context, event: &Event, widgets: &mut [&mut dyn Focusable]) -> Option<usize>{
+		if input_action::is_confirmation(event){
+			return self.activate(widgets);
+		}
+		let direction = match event{
+			Event::KeyPressed{key: Key::Tab} => {
+				if input::is_key_down(ctx, Key::LeftShift) || input::is_key_down(ctx, Key::RightShift){
+					self.previous(widgets);
+				}else{
+					self.next(widgets);
+				}
+				None
+			}
+			Event::KeyPressed{key: Key::Up} | Event::GamepadButtonPressed{button: GamepadButton::Up, ..} => Some(FocusDirection::Up),
+			Event::KeyPressed{key: Key::Down} | Event::GamepadButtonPressed{button: GamepadButton::Down, ..} => Some(FocusDirection::Down),
+			Event::KeyPressed{key: Key::Left} | Event::GamepadButtonPressed{button: GamepadButton::Left, ..} => Some(FocusDirection::Left),
+			Event::KeyPressed{key: Key::Right} | Event::GamepadButtonPressed{button: GamepadButton::Right, ..} => Some(FocusDirection::Right),
+			Event::GamepadAxisMoved{axis: GamepadAxis::LeftStickX, position, ..} => {
+				let previous = std::mem::replace(&mut self.stick.0, *position);
+				stick_direction(previous, *position, FocusDirection::Left, FocusDirection::Right)
+			}
+			Event::GamepadAxisMoved{axis: GamepadAxis::LeftStickY, position, ..} => {
+				let previous = std::mem::replace(&mut self.stick.1, *position);
+				stick_direction(previous, *position, FocusDirection::Up, FocusDirection::Down)
+			}
+			_ => None,
+		};
+		if let Some(direction) = direction{
+			self.move_focus(ctx, direction, widgets);
+		}
+		None
+	}
+
+	fn step(&self, widgets: &[&mut dyn Focusable], forward: bool) -> Option<usize>{
+		let len = widgets.len();
+		let mut index = self.focused;
+		for _ in 0..len{
+			let next = match (index, forward){
+				(None, true) => 0,
+				(None, false) => len - 1,
+				(Some(i), true) if i + 1 < len => i + 1,
+				(Some(i), false) if i > 0 => i - 1,
+				_ if !self.wrap => return self.focused,
+				(Some(_), true) => 0,
+				(Some(_), false) => len - 1,
+			};
+			if widgets[next].is_focusable(){
+				return Some(next);
+			}
+			index = Some(next);
+		}
+		self.focused
+	}
+}
+
+fn stick_direction(previous: f32, position: f32, negative: FocusDirection, positive: FocusDirection) -> Option<FocusDirection>{
+	if position >= STICK_THRESHOLD && previous < STICK_THRESHOLD{
+		Some(positive)
+	}else if position <= -STICK_THRESHOLD && previous > -STICK_THRESHOLD{
+		Some(negative)
+	}else{
+		None
+	}
+}
+
+// the widget with the smallest distance in the direction, the offset to the side counts double
+fn nearest(current: usize, direction: FocusDirection, bounds: &[Option<Rectangle>]) -> Option<usize>{
+	let center = |bounds: Rectangle| TetraVec2::new(bounds.x + bounds.width / 2.0, bounds.y + bounds.height / 2.0);
+	let from = center((*bounds.get(current)?)?);
+	let axis = direction.vector();
+	let mut best: Option<(usize, f32)> = None;
+	for (i, widget) in bounds.iter().enumerate(){
+		let widget = match widget{
+			Some(widget) if i != current => *widget,
+			_ => continue,
+		};
+		let offset = center(widget) - from;
+		let distance = offset.dot(axis);
+		if distance <= 0.0{
+			continue;
+		}
+		let side = (offset - axis * distance).magnitude();
+		let score = distance + side * 2.0;
+		if !matches!(best, Some((_, best)) if best <= score){
+			best = Some((i, score));
+		}
+	}
+	best.map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	struct Widget {
+		focusable: bool,
+		focused: bool,
+	}
+
+	impl Focusable for Widget {
+		fn get_bounds(&self, _ctx: &mut Context) -> Rectangle{
+			Rectangle::new(0.0, 0.0, 10.0, 10.0)
+		}
+
+		fn set_focused(&mut self, focused: bool){
+			self.focused = focused;
+		}
+
+		fn activate(&mut self){}
+
+		fn is_focusable(&self) -> bool{
+			self.focusable
+		}
+	}
+
+	fn widgets(focusable: &[bool]) -> Vec<Widget>{
+		focusable.iter().map(|f| Widget{focusable: *f, focused: false}).collect()
+	}
+
+	fn cell(x: f32, y: f32) -> Option<Rectangle>{
+		Some(Rectangle::new(x, y, 10.0, 10.0))
+	}
+
+	#[test]
+	fn step_wraps_and_skips(){
+		let mut widgets = widgets(&[true, false, true]);
+		let mut refs: Vec<&mut dyn Focusable> = widgets.iter_mut().map(|w| w as &mut dyn Focusable).collect();
+		let mut manager = FocusManager::new();
+		manager.next(&mut refs);
+		assert_eq!(manager.get_focused(), Some(0));
+		manager.next(&mut refs);
+		assert_eq!(manager.get_focused(), Some(2));
+		manager.next(&mut refs);
+		assert_eq!(manager.get_focused(), Some(0));
+		manager.previous(&mut refs);
+		assert_eq!(manager.get_focused(), Some(2));
+		drop(refs);
+		assert!(widgets[2].focused && !widgets[0].focused);
+	}
+
+	#[test]
+	fn step_stops_at_the_end_without_wrap(){
+		let mut widgets = widgets(&[true, true, false]);
+		let mut refs: Vec<&mut dyn Focusable> = widgets.iter_mut().map(|w| w as &mut dyn Focusable).collect();
+		let mut manager = FocusManager::new().wrap(false);
+		manager.set_focused(Some(1), &mut refs);
+		manager.next(&mut refs);
+		assert_eq!(manager.get_focused(), Some(1));
+		manager.previous(&mut refs);
+		manager.previous(&mut refs);
+		assert_eq!(manager.get_focused(), Some(0));
+	}
+
+	#[test]
+	fn nothing_focusable(){
+		let mut widgets = widgets(&[false, false]);
+		let mut refs: Vec<&mut dyn Focusable> = widgets.iter_mut().map(|w| w as &mut dyn Focusable).collect();
+		let mut manager = FocusManager::new();
+		manager.next(&mut refs);
+		assert_eq!(manager.get_focused(), None);
+	}
+
+	#[test]
+	fn nearest_prefers_the_widget_in_line(){
+		// 0 1
+		// 2 3, 4 is far right in the first row
+		let bounds = [cell(0.0, 0.0), cell(50.0, 0.0), cell(0.0, 50.0), cell(50.0, 50.0), cell(200.0, 0.0)];
+		assert_eq!(nearest(0, FocusDirection::Right, &bounds), Some(1));
+		assert_eq!(nearest(0, FocusDirection::Down, &bounds), Some(2));
+		assert_eq!(nearest(1, FocusDirection::Right, &bounds), Some(4));
+		assert_eq!(nearest(3, FocusDirection::Up, &bounds), Some(1));
+		assert_eq!(nearest(0, FocusDirection::Up, &bounds), None);
+	}
+
+	#[test]
+	fn nearest_weighs_the_side_offset(){
+		// 1 is closer, but 2 is in line
+		let bounds = [cell(0.0, 0.0), cell(30.0, 40.0), cell(80.0, 0.0)];
+		assert_eq!(nearest(0, FocusDirection::Right, &bounds), Some(2));
+	}
+
+	#[test]
+	fn nearest_skips_widgets_without_bounds(){
+		let bounds = [cell(0.0, 0.0), None, cell(0.0, 100.0)];
+		assert_eq!(nearest(0, FocusDirection::Down, &bounds), Some(2));
+		assert_eq!(nearest(1, FocusDirection::Down, &bounds), None);
+	}
+}
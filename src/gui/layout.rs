@@ -0,0 +1,491 @@
+use tetra::{Context, Event, window};
+use tetra::graphics::Rectangle;
+use crate::TetraVec2;
+
+/// a widget which can be placed by a layout
+pub trait Layoutable {
+	/// measured size, e.g. from the text bounds or the NineSlice
+	fn get_size(&self, ctx: &mut Context) -> TetraVec2;
+	/// the area has the measured size, the widget covers it like its Focusable bounds
+	fn set_layout_bounds(&mut self, ctx: &mut Context, bounds: Rectangle);
+}
+
+/// The widgets are passed by index, e.g. VBox::new().child(0).child(HBox::new().child(1).child(2)),
+/// a usize is the widget with this index.
+pub trait Layout {
+	fn measure(&self, ctx: &mut Context, widgets: &[&mut dyn Layoutable]) -> TetraVec2;
+	fn arrange(&self, ctx: &mut Context, area: Rectangle, widgets: &mut [&mut dyn Layoutable]);
+}
+
+impl Layout for usize {
+	fn measure(&self, ctx: &mut Context, widgets: &[&mut dyn Layoutable]) -> TetraVec2{
+		widgets.get(*self).map(|w| w.get_size(ctx)).unwrap_or_else(TetraVec2::zero)
+	}
+
+	fn arrange(&self, ctx: &mut Context, area: Rectangle, widgets: &mut [&mut dyn Layoutable]){
+		if let Some(widget) = widgets.get_mut(*self){
+			widget.set_layout_bounds(ctx, Rectangle::new(area.x.round(), area.y.round(), area.width, area.height));
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+	Start,
+	Center,
+	End,
+}
+
+impl Align {
+	// offset of an item with the size in the available space
+	fn offset(self, available: f32, size: f32) -> f32{
+		match self{
+			Align::Start => 0.0,
+			Align::Center => (available - size) / 2.0,
+			Align::End => available - size,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Padding {
+	pub left: f32,
+	pub top: f32,
+	pub right: f32,
+	pub bottom: f32,
+}
+
+impl Padding {
+	pub fn new(left: f32, top: f32, right: f32, bottom: f32) -> Padding{
+		Padding{
+			left,
+			top,
+			right,
+			bottom,
+		}
+	}
+
+	pub fn all(padding: f32) -> Padding{
+		Padding::new(padding, padding, padding, padding)
+	}
+
+	fn size(&self) -> TetraVec2{
+		TetraVec2::new(self.left + self.right, self.top + self.bottom)
+	}
+
+	fn shrink(&self, area: Rectangle) -> Rectangle{
+		Rectangle::new(area.x + self.left, area.y + self.top,
+			(area.width - self.left - self.right).max(0.0),
+			(area.height - self.top - self.bottom).max(0.0))
+	}
+}
+
+impl From<f32> for Padding {
+	fn from(padding: f32) -> Padding{
+		Padding::all(padding)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+	Vertical,
+	Horizontal,
+}
+
+/// Children in a column or a row, usually created with VBox::new() or HBox::new().
+pub struct Stack {
+	orientation: Orientation,
+	children: Vec<Box<dyn Layout>>,
+	padding: Padding,
+	spacing: f32,
+	align: Align,
+	justify: Align,
+}
+
+/// children from top to bottom
+pub struct VBox;
+/// children from left to right
+pub struct HBox;
+
+#[allow(clippy::new_ret_no_self)]
+impl VBox {
+	pub fn new() -> Stack{
+		Stack::new(Orientation::Vertical)
+	}
+}
+
+#[allow(clippy::new_ret_no_self)]
+impl HBox {
+	pub fn new() -> Stack{
+		Stack::new(Orientation::Horizontal)
+	}
+}
+
+#[allow(dead_code)]
+impl Stack {
+	pub fn new(orientation: Orientation) -> Stack{
+		Stack{
+			orientation,
+			children: Vec::new(),
+			padding: Padding::default(),
+			spacing: 0.0,
+			align: Align::Start,
+			justify: Align::Start,
+		}
+	}
+
+	pub fn child<L: Layout + 'static>(mut self, child: L) -> Self{
+		self.children.push(Box::new(child));
+		self
+	}
+
+	pub fn padding<P: Into<Padding>>(mut self, padding: P) -> Self{
+		self.padding = padding.into();
+		self
+	}
+
+	/// space between the children
+	pub fn spacing(mut self, spacing: f32) -> Self{
+		self.spacing = spacing;
+		self
+	}
+
+	/// alignment across the stack, e.g. centered buttons in a VBox
+	pub fn align(mut self, align: Align) -> Self{
+		self.align = align;
+		self
+	}
+
+	/// alignment of all children along the stack if there is more space than needed
+	pub fn justify(mut self, justify: Align) -> Self{
+		self.justify = justify;
+		self
+	}
+
+	// (main, cross) of a size
+	fn split(&self, size: TetraVec2) -> (f32, f32){
+		match self.orientation{
+			Orientation::Vertical => (size.y, size.x),
+			Orientation::Horizontal => (size.x, size.y),
+		}
+	}
+
+	fn join(&self, main: f32, cross: f32) -> TetraVec2{
+		match self.orientation{
+			Orientation::Vertical => TetraVec2::new(cross, main),
+			Orientation::Horizontal => TetraVec2::new(main, cross),
+		}
+	}
+
+	// size of the children with the measured sizes, including spacing and padding
+	fn size(&self, sizes: &[TetraVec2]) -> TetraVec2{
+		let (mut main, mut cross) = (0.0, 0.0_f32);
+		for size in sizes.iter(){
+			let (child_main, child_cross) = self.split(*size);
+			main += child_main;
+			cross = cross.max(child_cross);
+		}
+		main += self.spacing * sizes.len().saturating_sub(1) as f32;
+		self.join(main, cross) + self.padding.size()
+	}
+
+	// areas of the children with the measured sizes in the area of the stack
+	fn areas(&self, area: Rectangle, sizes: &[TetraVec2]) -> Vec<Rectangle>{
+		let content = self.padding.shrink(area);
+		let (available_main, available_cross) = self.split(TetraVec2::new(content.width, content.height));
+		let used = sizes.iter().map(|s| self.split(*s).0).sum::<f32>() + self.spacing * sizes.len().saturating_sub(1) as f32;
+		let mut main = self.justify.offset(available_main, used);
+		let mut areas = Vec::with_capacity(sizes.len());
+		for size in sizes.iter(){
+			let (child_main, child_cross) = self.split(*size);
+			let offset = self.join(main, self.align.offset(available_cross, child_cross));
+			areas.push(Rectangle::new(content.x + offset.x, content.y + offset.y, size.x, size.y));
+			main += child_main + self.spacing;
+		}
+		areas
+	}
+}
+
+impl Layout for Stack {
+	fn measure(&self, ctx: &mut Context, widgets: &[&mut dyn Layoutable]) -> TetraVec2{
+		let sizes: Vec<TetraVec2> = self.children.iter().map(|c| c.measure(ctx, widgets)).collect();
+		self.size(&sizes)
+	}
+
+	fn arrange(&self, ctx: &mut Context, area: Rectangle, widgets: &mut [&mut dyn Layoutable]){
+		let sizes: Vec<TetraVec2> = self.children.iter().map(|c| c.measure(ctx, widgets)).collect();
+		for (child, area) in self.children.iter().zip(self.areas(area, &sizes)){
+			child.arrange(ctx, area, widgets);
+		}
+	}
+}
+
+/// Children in rows with a fixed number of columns, every column is as wide as its widest child.
+pub struct Grid {
+	columns: usize,
+	children: Vec<Box<dyn Layout>>,
+	padding: Padding,
+	spacing: TetraVec2,
+	align: (Align, Align),
+}
+
+#[allow(dead_code)]
+impl Grid {
+	pub fn new(columns: usize) -> Grid{
+		Grid{
+			columns: columns.max(1),
+			children: Vec::new(),
+			padding: Padding::default(),
+			spacing: TetraVec2::zero(),
+			align: (Align::Start, Align::Start),
+		}
+	}
+
+	/// fills the rows from left to right
+	pub fn child<L: Layout + 'static>(mut self, child: L) -> Self{
+		self.children.push(Box::new(child));
+		self
+	}
+
+	pub fn padding<P: Into<Padding>>(mut self, padding: P) -> Self{
+		self.padding = padding.into();
+		self
+	}
+
+	pub fn spacing(mut self, x: f32, y: f32) -> Self{
+		self.spacing = TetraVec2::new(x, y);
+		self
+	}
+
+	/// alignment of a child in its cell
+	pub fn align(mut self, horizontal: Align, vertical: Align) -> Self{
+		self.align = (horizontal, vertical);
+		self
+	}
+
+	// widths of the columns and heights of the rows, usize::div_ceil needs rust 1.73
+	#[allow(clippy::manual_div_ceil)]
+	fn cells(&self, sizes: &[TetraVec2]) -> (Vec<f32>, Vec<f32>){
+		let rows = (sizes.len() + self.columns - 1) / self.columns;
+		let mut widths = vec![0.0_f32; self.columns];
+		let mut heights = vec![0.0_f32; rows];
+		for (i, size) in sizes.iter().enumerate(){
+			widths[i % self.columns] = widths[i % self.columns].max(size.x);
+			heights[i / self.columns] = heights[i / self.columns].max(size.y);
+		}
+		(widths, heights)
+	}
+
+	fn size(&self, sizes: &[TetraVec2]) -> TetraVec2{
+		let (widths, heights) = self.cells(sizes);
+		let width = widths.iter().sum::<f32>() + self.spacing.x * widths.len().saturating_sub(1) as f32;
+		let height = heights.iter().sum::<f32>() + self.spacing.y * heights.len().saturating_sub(1) as f32;
+		TetraVec2::new(width, height) + self.padding.size()
+	}
+
+	// areas of the children in their cells, the last row can be shorter
+	fn areas(&self, area: Rectangle, sizes: &[TetraVec2]) -> Vec<Rectangle>{
+		let content = self.padding.shrink(area);
+		let (widths, heights) = self.cells(sizes);
+		let mut areas = Vec::with_capacity(sizes.len());
+		let mut y = content.y;
+		for (row, height) in heights.iter().enumerate(){
+			let mut x = content.x;
+			for (column, width) in widths.iter().enumerate(){
+				if let Some(size) = sizes.get(row * self.columns + column){
+					let position = TetraVec2::new(x + self.align.0.offset(*width, size.x), y + self.align.1.offset(*height, size.y));
+					areas.push(Rectangle::new(position.x, position.y, size.x, size.y));
+				}
+				x += width + self.spacing.x;
+			}
+			y += height + self.spacing.y;
+		}
+		areas
+	}
+}
+
+impl Layout for Grid {
+	fn measure(&self, ctx: &mut Context, widgets: &[&mut dyn Layoutable]) -> TetraVec2{
+		let sizes: Vec<TetraVec2> = self.children.iter().map(|c| c.measure(ctx, widgets)).collect();
+		self.size(&sizes)
+	}
+
+	fn arrange(&self, ctx: &mut Context, area: Rectangle, widgets: &mut [&mut dyn Layoutable]){
+		let sizes: Vec<TetraVec2> = self.children.iter().map(|c| c.measure(ctx, widgets)).collect();
+		for (child, area) in self.children.iter().zip(self.areas(area, &sizes)){
+			child.arrange(ctx, area, widgets);
+		}
+	}
+}
+
+/// Places one child in the available area, e.g. a menu in the middle of the screen or a label in a corner.
+/// Takes the whole area, so it is mostly used at the root.
+pub struct Anchor {
+	child: Box<dyn Layout>,
+	padding: Padding,
+	align: (Align, Align),
+	offset: TetraVec2,
+}
+
+#[allow(dead_code)]
+impl Anchor {
+	/// centered by default
+	pub fn new<L: Layout + 'static>(child: L) -> Anchor{
+		Anchor{
+			child: Box::new(child),
+			padding: Padding::default(),
+			align: (Align::Center, Align::Center),
+			offset: TetraVec2::zero(),
+		}
+	}
+
+	/// e.g. align(Align::End, Align::Start) for the top right corner
+	pub fn align(mut self, horizontal: Align, vertical: Align) -> Self{
+		self.align = (horizontal, vertical);
+		self
+	}
+
+	/// distance to the border of the area
+	pub fn padding<P: Into<Padding>>(mut self, padding: P) -> Self{
+		self.padding = padding.into();
+		self
+	}
+
+	/// added to the aligned position
+	pub fn offset(mut self, x: f32, y: f32) -> Self{
+		self.offset = TetraVec2::new(x, y);
+		self
+	}
+
+	// area of the child with the measured size
+	fn child_area(&self, area: Rectangle, size: TetraVec2) -> Rectangle{
+		let content = self.padding.shrink(area);
+		let position = TetraVec2::new(
+			content.x + self.align.0.offset(content.width, size.x),
+			content.y + self.align.1.offset(content.height, size.y),
+		) + self.offset;
+		Rectangle::new(position.x, position.y, size.x, size.y)
+	}
+}
+
+impl Layout for Anchor {
+	fn measure(&self, ctx: &mut Context, widgets: &[&mut dyn Layoutable]) -> TetraVec2{
+		self.child.measure(ctx, widgets) + self.padding.size()
+	}
+
+	fn arrange(&self, ctx: &mut Context, area: Rectangle, widgets: &mut [&mut dyn Layoutable]){
+		let size = self.child.measure(ctx, widgets);
+		self.child.arrange(ctx, self.child_area(area, size), widgets);
+	}
+}
+
+/// Lays out the widgets in the screen and again when the size of the screen changes.
+pub struct LayoutRoot {
+	layout: Box<dyn Layout>,
+	size: Option<(i32, i32)>,
+}
+
+#[allow(dead_code)]
+impl LayoutRoot {
+	pub fn new<L: Layout + 'static>(layout: L) -> LayoutRoot{
+		LayoutRoot{
+			layout: Box::new(layout),
+			size: None,
+		}
+	}
+
+	/// the next update lays out the widgets again, e.g. after a text was changed
+	pub fn invalidate(&mut self){
+		self.size = None;
+	}
+
+	/// uses the window size
+	pub fn update(&mut self, ctx: &mut Context, widgets: &mut [&mut dyn Layoutable]){
+		let size = window::get_size(ctx);
+		self.update_size(ctx, size, widgets);
+	}
+
+	/// with a ScreenScaler the size is its inner size, e.g. update_size(ctx, scaler.inner_size(), widgets)
+	pub fn update_size(&mut self, ctx: &mut Context, size: (i32, i32), widgets: &mut [&mut dyn Layoutable]){
+		if self.size != Some(size){
+			self.size = Some(size);
+			self.layout.arrange(ctx, Rectangle::new(0.0, 0.0, size.0 as f32, size.1 as f32), widgets);
+		}
+	}
+
+	/// a resized window is laid out with the next update
+	pub fn event(&mut self, event: &Event){
+		if let Event::Resized{..} = event{
+			self.invalidate();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sizes(sizes: &[(f32, f32)]) -> Vec<TetraVec2>{
+		sizes.iter().map(|(x, y)| TetraVec2::new(*x, *y)).collect()
+	}
+
+	#[test]
+	fn align_and_padding(){
+		assert_eq!(Align::Start.offset(100.0, 40.0), 0.0);
+		assert_eq!(Align::Center.offset(100.0, 40.0), 30.0);
+		assert_eq!(Align::End.offset(100.0, 40.0), 60.0);
+		let padding = Padding::new(1.0, 2.0, 3.0, 4.0);
+		assert_eq!(padding.size(), TetraVec2::new(4.0, 6.0));
+		assert_eq!(padding.shrink(Rectangle::new(10.0, 10.0, 50.0, 50.0)), Rectangle::new(11.0, 12.0, 46.0, 44.0));
+		assert_eq!(padding.shrink(Rectangle::new(0.0, 0.0, 2.0, 2.0)), Rectangle::new(1.0, 2.0, 0.0, 0.0));
+	}
+
+	#[test]
+	fn vertical_stack(){
+		let stack = VBox::new().spacing(10.0).padding(5.0);
+		let children = sizes(&[(40.0, 20.0), (60.0, 30.0)]);
+		assert_eq!(stack.size(&children), TetraVec2::new(70.0, 70.0));
+		assert_eq!(stack.areas(Rectangle::new(0.0, 0.0, 70.0, 70.0), &children), vec![
+			Rectangle::new(5.0, 5.0, 40.0, 20.0),
+			Rectangle::new(5.0, 35.0, 60.0, 30.0),
+		]);
+	}
+
+	#[test]
+	fn stack_with_extra_space(){
+		let children = sizes(&[(20.0, 10.0), (30.0, 40.0)]);
+		let area = Rectangle::new(0.0, 0.0, 100.0, 60.0);
+		let stack = HBox::new().spacing(10.0).justify(Align::Center).align(Align::End);
+		assert_eq!(stack.size(&children), TetraVec2::new(60.0, 40.0));
+		assert_eq!(stack.areas(area, &children), vec![
+			Rectangle::new(20.0, 50.0, 20.0, 10.0),
+			Rectangle::new(50.0, 20.0, 30.0, 40.0),
+		]);
+		let stack = HBox::new().justify(Align::End).align(Align::Center);
+		assert_eq!(stack.areas(area, &children), vec![
+			Rectangle::new(50.0, 25.0, 20.0, 10.0),
+			Rectangle::new(70.0, 10.0, 30.0, 40.0),
+		]);
+	}
+
+	#[test]
+	fn grid_with_a_partial_last_row(){
+		let grid = Grid::new(2).spacing(4.0, 2.0).align(Align::Center, Align::End);
+		let children = sizes(&[(10.0, 10.0), (20.0, 5.0), (30.0, 8.0)]);
+		assert_eq!(grid.cells(&children), (vec![30.0, 20.0], vec![10.0, 8.0]));
+		assert_eq!(grid.size(&children), TetraVec2::new(54.0, 20.0));
+		assert_eq!(grid.areas(Rectangle::new(0.0, 0.0, 54.0, 20.0), &children), vec![
+			Rectangle::new(10.0, 0.0, 10.0, 10.0),
+			Rectangle::new(34.0, 5.0, 20.0, 5.0),
+			Rectangle::new(0.0, 12.0, 30.0, 8.0),
+		]);
+		assert_eq!(Grid::new(3).cells(&[]), (vec![0.0; 3], vec![]));
+	}
+
+	#[test]
+	fn anchor_in_a_corner(){
+		let anchor = Anchor::new(0).align(Align::End, Align::Start).padding(10.0).offset(0.0, 2.0);
+		let area = anchor.child_area(Rectangle::new(0.0, 0.0, 200.0, 100.0), TetraVec2::new(50.0, 20.0));
+		assert_eq!(area, Rectangle::new(140.0, 12.0, 50.0, 20.0));
+	}
+}
@@ -2,4 +2,6 @@ pub mod button;
 pub mod textbutton;
 pub mod fps;
 pub mod mouse;
+pub mod focus;
+pub mod layout;
 
@@ -3,17 +3,21 @@ use tetra::input::{self, MouseButton};
 use tetra::{Context};
 use tetra::math::Vec2;
 use tetra::graphics::text::{Text, Font};
+use crate::gui::focus::Focusable;
+use crate::gui::layout::Layoutable;
 
 #[allow(dead_code)]
 pub struct TextButton{
 	pressed: bool,
 	centered: bool,
 	visible: bool,
+	focused: bool,
 	text: Text,
 	color: Color,
 	normal_color: Color,
 	hover_color: Color,
 	pressed_color: Color,
+	focused_color: Color,
 	position: Vec2<f32>
 }
 
@@ -23,10 +27,12 @@ impl TextButton{
 			pressed: false,
 			centered: true,
 			visible: true,
+			focused: false,
 			color: Color::rgb(1.0,1.0,1.0),
 			normal_color: Color::rgb(1.0,1.0,1.0),
 			hover_color: Color::rgb(1.0, 0.0, 0.0),
 			pressed_color: Color::rgb(0.0, 0.8, 0.0),
+			focused_color: Color::rgb(1.0, 0.6, 0.0),
 			text: Text::new(text, font),
 			position,
 		})
@@ -63,6 +69,11 @@ impl TextButton{
 		self
 	}
 
+	pub fn focused_color(mut self, color: Color) -> Self{
+		self.focused_color = color;
+		self
+	}
+
 	pub fn visible(mut self, visible: bool) -> Self{
 		self.visible = visible;
 		self
@@ -100,6 +111,8 @@ impl TextButton{
 				if input::is_mouse_button_released(ctx, MouseButton::Left){
 					self.pressed = true;
 				}
+			}else if self.focused{
+				self.color = self.focused_color;
 			}else{
 				self.color = self.normal_color;
 			}
@@ -128,6 +141,47 @@ impl Drawable for TextButton {
 	}
 }
 
+impl Focusable for TextButton {
+	fn get_bounds(&self, ctx: &mut Context) -> Rectangle{
+		let bounds = self.text.get_bounds(ctx).unwrap_or_else(|| Rectangle::new(0.0, 0.0, 0.0, 0.0));
+		let mut position = self.position + Vec2::new(bounds.x, bounds.y);
+		if self.centered{
+			position -= Vec2::new(bounds.width / 2.0, bounds.height / 2.0).round();
+		}
+		Rectangle::new(position.x, position.y, bounds.width, bounds.height)
+	}
+
+	fn set_focused(&mut self, focused: bool){
+		self.focused = focused;
+	}
+
+	fn activate(&mut self){
+		self.pressed = true;
+	}
+
+	fn is_focusable(&self) -> bool{
+		self.visible
+	}
+}
+
+impl Layoutable for TextButton {
+	fn get_size(&self, ctx: &mut Context) -> Vec2<f32>{
+		match self.text.get_bounds(ctx){
+			Some(bounds) => Vec2::new(bounds.width, bounds.height),
+			None => Vec2::zero(),
+		}
+	}
+
+	// the text starts at the offset of its bounds, like in get_bounds
+	fn set_layout_bounds(&mut self, ctx: &mut Context, bounds: Rectangle){
+		let text = self.text.get_bounds(ctx).unwrap_or_else(|| Rectangle::new(0.0, 0.0, 0.0, 0.0));
+		self.position = Vec2::new(bounds.x - text.x, bounds.y - text.y);
+		if self.centered{
+			self.position += Vec2::new(text.width / 2.0, text.height / 2.0).round();
+		}
+	}
+}
+
 fn is_inside_hover_area(centered: bool, draw_position: Vec2<f32>, area: Rectangle, position: Vec2<f32>) -> bool{
 	let mut pos_x = draw_position.x;
 	let mut pos_y = draw_position.y;
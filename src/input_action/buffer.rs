@@ -0,0 +1,335 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+use tetra::{Context, time};
+use log::debug;
+use crate::input_action::map::ActionMap;
+
+struct ComboStep {
+	actions: Vec<String>,
+	within: Duration,
+}
+
+/// A sequence of steps, every step is one action or a chord of actions which are pressed together,
+/// e.g. Combo::new("fireball").then(&["down"], ms(0)).then(&["down_forward"], ms(150)).then(&["forward", "punch"], ms(150))
+pub struct Combo {
+	name: String,
+	steps: Vec<ComboStep>,
+	chord: Duration,
+	finished: Option<Duration>,
+}
+
+#[allow(dead_code)]
+impl Combo {
+	pub fn new(name: &str) -> Combo{
+		Combo{
+			name: name.to_string(),
+			steps: Vec::new(),
+			chord: Duration::from_millis(50),
+			finished: None,
+		}
+	}
+
+	/// the step has to follow the previous step within the time, it is ignored for the first step
+	pub fn then(mut self, actions: &[&str], within: Duration) -> Self{
+		self.steps.push(ComboStep{
+			actions: actions.iter().map(|a| a.to_string()).collect(),
+			within,
+		});
+		self
+	}
+
+	/// max time between the actions of one step, default is 50ms
+	pub fn chord(mut self, chord: Duration) -> Self{
+		self.chord = chord;
+		self
+	}
+
+	pub fn get_name(&self) -> &str{
+		&self.name
+	}
+
+	// time which the presses of the combo need in the history
+	fn length(&self) -> Duration{
+		self.steps.iter().skip(1).map(|s| s.within + self.chord).sum::<Duration>() + self.chord
+	}
+}
+
+/// Records the pressed actions with a timestamp, e.g. a jump which was pressed shortly before landing.
+/// The clock only advances with update (tetra delta time) or tick (manual step).
+pub struct InputBuffer {
+	time: Duration,
+	history: VecDeque<(String, Duration)>,
+	length: Duration,
+	combos: Vec<Combo>,
+	events: Vec<String>,
+}
+
+#[allow(dead_code)]
+impl InputBuffer {
+	pub fn new() -> InputBuffer{
+		InputBuffer{
+			time: Duration::from_secs(0),
+			history: VecDeque::new(),
+			length: Duration::from_secs(1),
+			combos: Vec::new(),
+			events: Vec::new(),
+		}
+	}
+
+	/// how long the presses are kept, default is one second, combos can keep them longer
+	pub fn length(mut self, length: Duration) -> Self{
+		self.length = length;
+		self
+	}
+
+	pub fn combo(mut self, combo: Combo) -> Self{
+		self.combos.push(combo);
+		self
+	}
+
+	/// records the pressed actions of the map, call it after ActionMap::update
+	pub fn update(&mut self, ctx: &Context, actions: &ActionMap){
+		self.update_by(time::get_delta_time(ctx), actions);
+	}
+
+	/// like update with the delta of a replay, see InputRecorder::get_frame_delta
+	pub fn update_by(&mut self, delta: Duration, actions: &ActionMap){
+		self.tick(delta);
+		let pressed: Vec<String> = actions.get_actions()
+			.filter(|a| actions.is_pressed(a))
+			.map(|a| a.to_string())
+			.collect();
+		for action in pressed{
+			self.press(&action);
+		}
+	}
+
+	pub fn tick(&mut self, delta: Duration){
+		self.time += delta;
+		let keep = self.combos.iter().map(|c| c.length()).fold(self.length, Duration::max);
+		while let Some((_, pressed)) = self.history.front(){
+			if self.time - *pressed > keep{
+				self.history.pop_front();
+			}else{
+				break;
+			}
+		}
+	}
+
+	/// records a press at the current time, e.g. from events or a simulated input
+	pub fn press(&mut self, action: &str){
+		self.history.push_back((action.to_string(), self.time));
+		self.check_combos();
+	}
+
+	/// time since the start of the buffer
+	pub fn get_time(&self) -> Duration{
+		self.time
+	}
+
+	pub fn was_pressed_within(&self, action: &str, within: Duration) -> bool{
+		self.history.iter().rev().any(|(a, pressed)| a == action && self.time - *pressed <= within)
+	}
+
+	/// like was_pressed_within, but the press is removed so it is only used once
+	pub fn consume(&mut self, action: &str, within: Duration) -> bool{
+		let time = self.time;
+		match self.history.iter().rposition(|(a, pressed)| a == action && time - *pressed <= within){
+			Some(index) => {
+				self.history.remove(index);
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub fn clear(&mut self){
+		self.history.clear();
+	}
+
+	/// names of the combos which were finished since the last call
+	pub fn events(&mut self) -> Vec<String>{
+		std::mem::take(&mut self.events)
+	}
+
+	fn check_combos(&mut self){
+		for i in 0..self.combos.len(){
+			if self.is_finished(&self.combos[i]){
+				let combo = &mut self.combos[i];
+				debug!("combo {}", combo.name);
+				combo.finished = Some(self.time);
+				self.events.push(combo.name.clone());
+			}
+		}
+	}
+
+	// one action of the last step has to be pressed now, the steps before are searched backwards,
+	// presses in the same frame count as in order (in any order of the press calls), but a press is only used once,
+	// presses before the last time the combo was finished are not used again
+	fn is_finished(&self, combo: &Combo) -> bool{
+		let now = self.time;
+		match combo.steps.last(){
+			Some(step) if self.history.iter().rev()
+				.take_while(|(_, pressed)| *pressed == now)
+				.any(|(action, _)| step.actions.contains(action)) => {}
+			_ => return false,
+		}
+		let mut used: Vec<usize> = Vec::new();
+		let mut before: Option<Duration> = None;
+		let mut following: Option<(Duration, Duration)> = None;
+		for step in combo.steps.iter().rev(){
+			let mut range: Option<(Duration, Duration)> = None;
+			for action in step.actions.iter(){
+				let pressed = self.history.iter().enumerate().rev()
+					.find(|(i, (a, pressed))| a == action && !used.contains(i) && !matches!(before, Some(b) if *pressed > b))
+					.map(|(i, (_, pressed))| (i, *pressed));
+				match (pressed, combo.finished){
+					(Some((_, pressed)), Some(finished)) if pressed <= finished => return false,
+					(Some((i, pressed)), _) => {
+						used.push(i);
+						range = Some(match range{
+							Some((first, last)) => (first.min(pressed), last.max(pressed)),
+							None => (pressed, pressed),
+						});
+					}
+					(None, _) => return false,
+				}
+			}
+			let (first, last) = match range{
+				Some(range) => range,
+				None => return false,
+			};
+			if last - first > combo.chord{
+				return false;
+			}
+			if let Some((next_first, within)) = following{
+				if next_first - last > within{
+					return false;
+				}
+			}
+			following = Some((first, step.within));
+			before = Some(first);
+		}
+		true
+	}
+}
+
+impl Default for InputBuffer {
+	fn default() -> InputBuffer {
+		InputBuffer::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn ms(ms: u64) -> Duration{
+		Duration::from_millis(ms)
+	}
+
+	fn fireball() -> Combo{
+		Combo::new("fireball")
+			.then(&["down"], ms(0))
+			.then(&["down_forward"], ms(150))
+			.then(&["forward", "punch"], ms(150))
+	}
+
+	#[test]
+	fn pressed_within_the_edge_of_the_window(){
+		let mut buffer = InputBuffer::new();
+		buffer.press("jump");
+		buffer.tick(ms(100));
+		assert!(buffer.was_pressed_within("jump", ms(100)));
+		assert!(!buffer.was_pressed_within("jump", ms(99)));
+		assert!(!buffer.was_pressed_within("fire", ms(100)));
+	}
+
+	#[test]
+	fn consume_is_single_use(){
+		let mut buffer = InputBuffer::new();
+		buffer.press("jump");
+		buffer.tick(ms(50));
+		assert!(buffer.consume("jump", ms(100)));
+		assert!(!buffer.consume("jump", ms(100)));
+		assert!(!buffer.was_pressed_within("jump", ms(100)));
+	}
+
+	#[test]
+	fn combo_within_the_windows(){
+		let mut buffer = InputBuffer::new().combo(fireball());
+		buffer.press("down");
+		buffer.tick(ms(150));
+		buffer.press("down_forward");
+		buffer.tick(ms(100));
+		buffer.press("forward");
+		buffer.tick(ms(20));
+		buffer.press("punch");
+		assert_eq!(buffer.events(), vec!["fireball".to_string()]);
+		assert!(buffer.events().is_empty());
+	}
+
+	#[test]
+	fn combo_fails_when_a_window_is_missed(){
+		let mut buffer = InputBuffer::new().combo(fireball());
+		buffer.press("down");
+		buffer.tick(ms(151));
+		buffer.press("down_forward");
+		buffer.tick(ms(100));
+		buffer.press("forward");
+		buffer.press("punch");
+		assert!(buffer.events().is_empty());
+	}
+
+	#[test]
+	fn chord_tolerance(){
+		let punch_kick = || Combo::new("special").then(&["punch", "kick"], ms(0));
+		let mut buffer = InputBuffer::new().combo(punch_kick());
+		buffer.press("punch");
+		buffer.tick(ms(60));
+		buffer.press("kick");
+		assert!(buffer.events().is_empty());
+
+		let mut buffer = InputBuffer::new().combo(punch_kick().chord(ms(60)));
+		buffer.press("punch");
+		buffer.tick(ms(60));
+		buffer.press("kick");
+		assert_eq!(buffer.events(), vec!["special".to_string()]);
+	}
+
+	#[test]
+	fn presses_are_not_reused_after_the_combo(){
+		let mut buffer = InputBuffer::new().combo(fireball());
+		buffer.press("down");
+		buffer.tick(ms(100));
+		buffer.press("down_forward");
+		buffer.tick(ms(100));
+		buffer.press("forward");
+		buffer.press("punch");
+		assert_eq!(buffer.events().len(), 1);
+		buffer.tick(ms(10));
+		buffer.press("punch");
+		buffer.tick(ms(10));
+		buffer.press("forward");
+		assert!(buffer.events().is_empty());
+	}
+
+	#[test]
+	fn presses_in_the_same_frame(){
+		// the steps are in order even if the presses of one frame arrive in another order
+		let mut buffer = InputBuffer::new().combo(fireball());
+		buffer.press("down");
+		buffer.tick(ms(100));
+		buffer.press("punch");
+		buffer.press("forward");
+		buffer.press("down_forward");
+		assert_eq!(buffer.events(), vec!["fireball".to_string()]);
+
+		// one press can not be used for two steps
+		let mut buffer = InputBuffer::new().combo(Combo::new("double").then(&["down"], ms(0)).then(&["down"], ms(100)));
+		buffer.press("down");
+		assert!(buffer.events().is_empty());
+		buffer.press("down");
+		assert_eq!(buffer.events(), vec!["double".to_string()]);
+	}
+}
@@ -0,0 +1,362 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use tetra::{Context, Event, input};
+use tetra::input::{GamepadAxis, GamepadButton, Key, MouseButton};
+use log::debug;
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "ron_file")]
+use std::path::Path;
+#[cfg(feature = "ron_file")]
+use crate::utils::ron::{self, RonFileError};
+
+// an action counts as down above this value
+const PRESS_THRESHOLD: f32 = 0.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub enum AxisDirection {
+	Positive,
+	Negative,
+}
+
+/// an input which can be bound to an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub enum Binding {
+	Key(Key),
+	Mouse(MouseButton),
+	Button(GamepadButton),
+	/// one direction of an axis, e.g. Axis(GamepadAxis::LeftStickX, AxisDirection::Negative) for left
+	Axis(GamepadAxis, AxisDirection),
+}
+
+impl Binding {
+	/// the binding of a pressed key or button, e.g. to rebind an action to the next input of the player
+	pub fn from_event(event: &Event) -> Option<Binding>{
+		match event{
+			Event::KeyPressed{key} => Some(Binding::Key(*key)),
+			Event::MouseButtonPressed{button} => Some(Binding::Mouse(*button)),
+			Event::GamepadButtonPressed{button, ..} => Some(Binding::Button(*button)),
+			Event::GamepadAxisMoved{axis, position, ..} if position.abs() >= PRESS_THRESHOLD => {
+				let direction = if *position > 0.0 { AxisDirection::Positive } else { AxisDirection::Negative };
+				Some(Binding::Axis(*axis, direction))
+			}
+			_ => None,
+		}
+	}
+
+	/// 0.0 - 1.0, buttons are 0.0 or 1.0
+	pub fn get_value(&self, ctx: &Context, gamepad: usize, deadzone: f32) -> f32{
+		let down = |down: bool| if down { 1.0 } else { 0.0 };
+		match *self{
+			Binding::Key(key) => down(input::is_key_down(ctx, key)),
+			Binding::Mouse(button) => down(input::is_mouse_button_down(ctx, button)),
+			Binding::Button(button) => down(input::is_gamepad_button_down(ctx, gamepad, button)),
+			Binding::Axis(axis, direction) => {
+				let position = input::get_gamepad_axis_position(ctx, gamepad, axis);
+				let value = match direction{
+					AxisDirection::Positive => position,
+					AxisDirection::Negative => -position,
+				};
+				if value < deadzone{
+					0.0
+				}else{
+					((value - deadzone) / (1.0 - deadzone)).min(1.0)
+				}
+			}
+		}
+	}
+}
+
+/// the binding is already used by another action
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingConflict {
+	pub binding: Binding,
+	pub action: String,
+}
+
+impl fmt::Display for BindingConflict {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{:?} is already bound to {}", self.binding, self.action)
+	}
+}
+
+impl std::error::Error for BindingConflict {}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct ActionState {
+	value: f32,
+	previous: f32,
+}
+
+/// Named actions which are bound to keys, mouse buttons, gamepad buttons and gamepad axes.
+/// Call update once per frame before the queries.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub struct ActionMap {
+	bindings: BTreeMap<String, Vec<Binding>>,
+	#[cfg_attr(feature = "ron_file", serde(default))]
+	gamepad: usize,
+	#[cfg_attr(feature = "ron_file", serde(default = "default_deadzone"))]
+	deadzone: f32,
+	#[cfg_attr(feature = "ron_file", serde(skip))]
+	states: HashMap<String, ActionState>,
+}
+
+fn default_deadzone() -> f32{
+	0.2
+}
+
+#[allow(dead_code)]
+impl ActionMap {
+	pub fn new() -> ActionMap{
+		ActionMap{
+			bindings: BTreeMap::new(),
+			gamepad: 0,
+			deadzone: default_deadzone(),
+			states: HashMap::new(),
+		}
+	}
+
+	/// e.g. ActionMap::new().bind("jump", Binding::Key(Key::Space)).bind("jump", Binding::Button(GamepadButton::A))
+	pub fn bind(mut self, action: &str, binding: Binding) -> Self{
+		self.add_binding(action, binding);
+		self
+	}
+
+	/// which gamepad is used, default is 0
+	pub fn gamepad(mut self, id: usize) -> Self{
+		self.gamepad = id;
+		self
+	}
+
+	/// axis values below the deadzone are 0.0, default is 0.2
+	pub fn deadzone(mut self, deadzone: f32) -> Self{
+		self.deadzone = deadzone.clamp(0.0, 0.99);
+		self
+	}
+
+	pub fn get_gamepad(&self) -> usize{
+		self.gamepad
+	}
+
+	pub fn get_deadzone(&self) -> f32{
+		self.deadzone
+	}
+
+	#[cfg(feature = "ron_file")]
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<ActionMap, RonFileError>{
+		ron::load_file(path)
+	}
+
+	#[cfg(feature = "ron_file")]
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RonFileError>{
+		ron::save_file(path, self)
+	}
+
+	pub fn add_binding(&mut self, action: &str, binding: Binding){
+		let bindings = self.bindings.entry(action.to_string()).or_default();
+		if !bindings.contains(&binding){
+			bindings.push(binding);
+		}
+	}
+
+	pub fn remove_binding(&mut self, action: &str, binding: Binding){
+		if let Some(bindings) = self.bindings.get_mut(action){
+			bindings.retain(|b| *b != binding);
+		}
+	}
+
+	/// removes all bindings, the action stays in the map
+	pub fn clear_bindings(&mut self, action: &str){
+		if let Some(bindings) = self.bindings.get_mut(action){
+			bindings.clear();
+		}
+	}
+
+	pub fn get_bindings(&self, action: &str) -> &[Binding]{
+		self.bindings.get(action).map(|b| b.as_slice()).unwrap_or(&[])
+	}
+
+	pub fn get_actions(&self) -> impl Iterator<Item = &str>{
+		self.bindings.keys().map(|a| a.as_str())
+	}
+
+	/// the other action which uses the binding
+	pub fn find_conflict(&self, action: &str, binding: Binding) -> Option<&str>{
+		self.bindings.iter()
+			.find(|(name, bindings)| name.as_str() != action && bindings.contains(&binding))
+			.map(|(name, _)| name.as_str())
+	}
+
+	/// replaces the binding at the index (or adds it), fails if another action uses the binding
+	pub fn rebind(&mut self, action: &str, index: usize, binding: Binding) -> Result<(), BindingConflict>{
+		if let Some(other) = self.find_conflict(action, binding){
+			return Err(BindingConflict{
+				binding,
+				action: other.to_string(),
+			});
+		}
+		self.set_binding(action, index, binding);
+		Ok(())
+	}
+
+	/// like rebind, but the other action gets the old binding at the same place
+	pub fn rebind_swap(&mut self, action: &str, index: usize, binding: Binding){
+		let old = self.get_bindings(action).get(index).copied().filter(|old| *old != binding);
+		if let Some(other) = self.find_conflict(action, binding).map(|o| o.to_string()){
+			if let Some(bindings) = self.bindings.get_mut(&other){
+				match old{
+					Some(old) if !bindings.contains(&old) => {
+						for b in bindings.iter_mut().filter(|b| **b == binding){
+							*b = old;
+						}
+					}
+					_ => bindings.retain(|b| *b != binding),
+				}
+			}
+			debug!("swap {:?} with {}", binding, other);
+		}
+		self.set_binding(action, index, binding);
+	}
+
+	// replaces the binding at the index, a copy of the binding at another index is removed afterwards
+	fn set_binding(&mut self, action: &str, index: usize, binding: Binding){
+		let bindings = self.bindings.entry(action.to_string()).or_default();
+		if index < bindings.len(){
+			bindings[index] = binding;
+			let mut i = 0;
+			bindings.retain(|b| {
+				let keep = *b != binding || i == index;
+				i += 1;
+				keep
+			});
+		}else if !bindings.contains(&binding){
+			bindings.push(binding);
+		}
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		let (gamepad, deadzone) = (self.gamepad, self.deadzone);
+		self.update_with(|binding| binding.get_value(ctx, gamepad, deadzone));
+	}
+
+	/// reads the value of every binding from another source than tetra
+	pub fn update_with<F>(&mut self, mut read: F)
+		where
+			F: FnMut(&Binding) -> f32,
+	{
+		for (action, bindings) in self.bindings.iter(){
+			let value = bindings.iter().map(&mut read).fold(0.0, f32::max);
+			let state = self.states.entry(action.clone()).or_default();
+			state.previous = state.value;
+			state.value = value;
+		}
+	}
+
+	/// 0.0 - 1.0, the highest value of the bindings
+	pub fn get_value(&self, action: &str) -> f32{
+		self.states.get(action).map(|s| s.value).unwrap_or(0.0)
+	}
+
+	/// e.g. get_axis("left", "right") for a horizontal movement between -1.0 and 1.0
+	pub fn get_axis(&self, negative: &str, positive: &str) -> f32{
+		self.get_value(positive) - self.get_value(negative)
+	}
+
+	/// held down
+	pub fn is_down(&self, action: &str) -> bool{
+		self.get_value(action) >= PRESS_THRESHOLD
+	}
+
+	/// down since this frame
+	pub fn is_pressed(&self, action: &str) -> bool{
+		matches!(self.states.get(action), Some(s) if s.value >= PRESS_THRESHOLD && s.previous < PRESS_THRESHOLD)
+	}
+
+	/// up since this frame
+	pub fn is_released(&self, action: &str) -> bool{
+		matches!(self.states.get(action), Some(s) if s.value < PRESS_THRESHOLD && s.previous >= PRESS_THRESHOLD)
+	}
+}
+
+impl Default for ActionMap {
+	fn default() -> ActionMap {
+		ActionMap::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const A: Binding = Binding::Key(Key::A);
+	const B: Binding = Binding::Key(Key::B);
+	const C: Binding = Binding::Key(Key::C);
+	const SPACE: Binding = Binding::Key(Key::Space);
+
+	#[test]
+	fn rebind_replaces_the_index(){
+		let mut map = ActionMap::new().bind("move", A).bind("move", B).bind("move", C);
+		map.rebind("move", 2, A).unwrap();
+		assert_eq!(map.get_bindings("move"), &[B, A]);
+		map.rebind("move", 0, SPACE).unwrap();
+		assert_eq!(map.get_bindings("move"), &[SPACE, A]);
+		map.rebind("move", 5, C).unwrap();
+		map.rebind("move", 5, C).unwrap();
+		assert_eq!(map.get_bindings("move"), &[SPACE, A, C]);
+		map.rebind("new", 0, B).unwrap();
+		assert_eq!(map.get_bindings("new"), &[B]);
+	}
+
+	#[test]
+	fn conflicts(){
+		let mut map = ActionMap::new().bind("jump", SPACE).bind("fire", A);
+		assert_eq!(map.find_conflict("fire", SPACE), Some("jump"));
+		assert_eq!(map.find_conflict("jump", SPACE), None);
+		assert_eq!(map.rebind("fire", 0, SPACE), Err(BindingConflict{ binding: SPACE, action: "jump".to_string() }));
+		assert_eq!(map.get_bindings("fire"), &[A]);
+		assert_eq!(map.get_bindings("jump"), &[SPACE]);
+	}
+
+	#[test]
+	fn rebind_swap(){
+		let mut map = ActionMap::new().bind("jump", B).bind("jump", SPACE).bind("fire", A);
+		map.rebind_swap("fire", 0, SPACE);
+		assert_eq!(map.get_bindings("fire"), &[SPACE]);
+		assert_eq!(map.get_bindings("jump"), &[B, A]);
+
+		// the other action already has the old binding
+		let mut map = ActionMap::new().bind("jump", A).bind("jump", SPACE).bind("fire", A);
+		map.rebind_swap("fire", 0, SPACE);
+		assert_eq!(map.get_bindings("fire"), &[SPACE]);
+		assert_eq!(map.get_bindings("jump"), &[A]);
+
+		// nothing to swap for a new binding
+		let mut map = ActionMap::new().bind("jump", SPACE);
+		map.rebind_swap("fire", 0, SPACE);
+		assert_eq!(map.get_bindings("fire"), &[SPACE]);
+		assert!(map.get_bindings("jump").is_empty());
+	}
+
+	#[cfg(feature = "ron_file")]
+	#[test]
+	fn ron_round_trip(){
+		let map = ActionMap::new()
+			.bind("jump", SPACE)
+			.bind("left", Binding::Axis(GamepadAxis::LeftStickX, AxisDirection::Negative))
+			.bind("fire", Binding::Mouse(MouseButton::Left))
+			.bind("fire", Binding::Button(GamepadButton::A))
+			.gamepad(1)
+			.deadzone(0.3);
+		let path = std::env::temp_dir().join(format!("tetrapack_{}_actions.ron", std::process::id()));
+		map.save(&path).unwrap();
+		let loaded = ActionMap::load(&path);
+		let _ = std::fs::remove_file(&path);
+		let loaded = loaded.unwrap();
+		assert_eq!(loaded.bindings, map.bindings);
+		assert_eq!(loaded.get_gamepad(), 1);
+		assert_eq!(loaded.get_deadzone(), 0.3);
+	}
+}
@@ -3,6 +3,10 @@ use tetra::{
 	input::{GamepadButton, Key}
 };
 
+pub mod buffer;
+pub mod map;
+pub mod recording;
+
 #[allow(dead_code)]
 pub fn is_confirmation(event: &Event) -> bool{
 	match event{
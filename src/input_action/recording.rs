@@ -0,0 +1,326 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use tetra::{time, Context, Event};
+use tetra::input::{GamepadAxis, GamepadButton, GamepadStick, Key, MouseButton};
+use log::debug;
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "ron_file")]
+use std::path::Path;
+#[cfg(feature = "ron_file")]
+use crate::utils::ron::{self, RonFileError};
+use crate::input_action::map::{ActionMap, Binding};
+use crate::TetraVec2;
+
+/// the input events of tetra which are recorded, positions are stored as (x, y)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub enum RecordedEvent {
+	KeyPressed(Key),
+	KeyReleased(Key),
+	MouseButtonPressed(MouseButton),
+	MouseButtonReleased(MouseButton),
+	MouseMoved((f32, f32), (f32, f32)),
+	MouseWheelMoved((i32, i32)),
+	GamepadButtonPressed(usize, GamepadButton),
+	GamepadButtonReleased(usize, GamepadButton),
+	GamepadAxisMoved(usize, GamepadAxis, f32),
+	GamepadStickMoved(usize, GamepadStick, (f32, f32)),
+	TextInput(String),
+}
+
+impl RecordedEvent {
+	/// None for events which are not input, e.g. Resized
+	pub fn from_event(event: &Event) -> Option<RecordedEvent>{
+		Some(match event{
+			Event::KeyPressed{key} => RecordedEvent::KeyPressed(*key),
+			Event::KeyReleased{key} => RecordedEvent::KeyReleased(*key),
+			Event::MouseButtonPressed{button} => RecordedEvent::MouseButtonPressed(*button),
+			Event::MouseButtonReleased{button} => RecordedEvent::MouseButtonReleased(*button),
+			Event::MouseMoved{position, delta} => RecordedEvent::MouseMoved((position.x, position.y), (delta.x, delta.y)),
+			Event::MouseWheelMoved{amount} => RecordedEvent::MouseWheelMoved((amount.x, amount.y)),
+			Event::GamepadButtonPressed{id, button} => RecordedEvent::GamepadButtonPressed(*id, *button),
+			Event::GamepadButtonReleased{id, button} => RecordedEvent::GamepadButtonReleased(*id, *button),
+			Event::GamepadAxisMoved{id, axis, position} => RecordedEvent::GamepadAxisMoved(*id, *axis, *position),
+			Event::GamepadStickMoved{id, stick, position} => RecordedEvent::GamepadStickMoved(*id, *stick, (position.x, position.y)),
+			Event::TextInput{text} => RecordedEvent::TextInput(text.clone()),
+			_ => return None,
+		})
+	}
+
+	pub fn to_event(&self) -> Event{
+		match self.clone(){
+			RecordedEvent::KeyPressed(key) => Event::KeyPressed{key},
+			RecordedEvent::KeyReleased(key) => Event::KeyReleased{key},
+			RecordedEvent::MouseButtonPressed(button) => Event::MouseButtonPressed{button},
+			RecordedEvent::MouseButtonReleased(button) => Event::MouseButtonReleased{button},
+			RecordedEvent::MouseMoved((x, y), (dx, dy)) => Event::MouseMoved{
+				position: TetraVec2::new(x, y),
+				delta: TetraVec2::new(dx, dy),
+			},
+			RecordedEvent::MouseWheelMoved((x, y)) => Event::MouseWheelMoved{amount: tetra::math::Vec2::new(x, y)},
+			RecordedEvent::GamepadButtonPressed(id, button) => Event::GamepadButtonPressed{id, button},
+			RecordedEvent::GamepadButtonReleased(id, button) => Event::GamepadButtonReleased{id, button},
+			RecordedEvent::GamepadAxisMoved(id, axis, position) => Event::GamepadAxisMoved{id, axis, position},
+			RecordedEvent::GamepadStickMoved(id, stick, (x, y)) => Event::GamepadStickMoved{id, stick, position: TetraVec2::new(x, y)},
+			RecordedEvent::TextInput(text) => Event::TextInput{text},
+		}
+	}
+}
+
+/// the events before the update of a frame and the values of the bindings which were down
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub struct RecordedFrame {
+	pub delta: Duration,
+	#[cfg_attr(feature = "ron_file", serde(default))]
+	pub events: Vec<RecordedEvent>,
+	#[cfg_attr(feature = "ron_file", serde(default))]
+	pub bindings: Vec<(Binding, f32)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub struct Recording {
+	/// seed for the random number generator of the game, see create_rng,
+	/// tetrapack seeds its own generator with it (utils::random)
+	pub seed: u64,
+	pub frames: Vec<RecordedFrame>,
+}
+
+#[allow(dead_code)]
+impl Recording {
+	#[cfg(feature = "ron_file")]
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<Recording, RonFileError>{
+		ron::load_file(path)
+	}
+
+	#[cfg(feature = "ron_file")]
+	pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), RonFileError>{
+		ron::save_file(path, self)
+	}
+
+	/// the same seed gives the same random numbers in the replay
+	#[cfg(feature = "randomize")]
+	pub fn create_rng(&self) -> rand::rngs::StdRng{
+		use rand::SeedableRng;
+		rand::rngs::StdRng::seed_from_u64(self.seed)
+	}
+
+	pub fn len(&self) -> usize{
+		self.frames.len()
+	}
+
+	pub fn is_empty(&self) -> bool{
+		self.frames.is_empty()
+	}
+}
+
+enum Mode {
+	Idle,
+	Recording(Recording),
+	Replaying(Recording),
+}
+
+struct RecorderState {
+	mode: Mode,
+	frame: usize,
+	// events which arrived before the next update
+	pending: Vec<RecordedEvent>,
+	stop_on_input: bool,
+}
+
+/// Records the input for the SceneManager or replays it as if a player were at the keyboard.
+/// Shared handle like the Mixer, give one clone to the SceneManager and use another one in the scenes,
+/// update the ActionMaps with update_actions so the input state is recorded and replayed too.
+#[derive(Clone)]
+pub struct InputRecorder {
+	state: Rc<RefCell<RecorderState>>,
+}
+
+#[allow(dead_code)]
+impl InputRecorder {
+	pub fn new() -> InputRecorder{
+		InputRecorder{
+			state: Rc::new(RefCell::new(RecorderState{
+				mode: Mode::Idle,
+				frame: 0,
+				pending: Vec::new(),
+				stop_on_input: false,
+			})),
+		}
+	}
+
+	/// a key or button of the player stops the replay, e.g. for an attract mode
+	pub fn stop_on_input(self, stop: bool) -> Self{
+		self.state.borrow_mut().stop_on_input = stop;
+		self
+	}
+
+	pub fn start_recording(&self, seed: u64){
+		let mut state = self.state.borrow_mut();
+		state.mode = Mode::Recording(Recording{
+			seed,
+			frames: Vec::new(),
+		});
+		state.frame = 0;
+		state.pending.clear();
+		#[cfg(feature = "randomize")]
+		crate::utils::random::seed(seed);
+		debug!("start recording with seed {}", seed);
+	}
+
+	pub fn replay(&self, recording: Recording){
+		let mut state = self.state.borrow_mut();
+		debug!("replay {} frames", recording.frames.len());
+		#[cfg(feature = "randomize")]
+		crate::utils::random::seed(recording.seed);
+		state.mode = Mode::Replaying(recording);
+		state.frame = 0;
+		state.pending.clear();
+	}
+
+	/// stops the recording or the replay, returns the recording
+	pub fn stop(&self) -> Option<Recording>{
+		let mut state = self.state.borrow_mut();
+		match std::mem::replace(&mut state.mode, Mode::Idle){
+			Mode::Recording(recording) | Mode::Replaying(recording) => Some(recording),
+			Mode::Idle => None,
+		}
+	}
+
+	pub fn is_recording(&self) -> bool{
+		matches!(self.state.borrow().mode, Mode::Recording(_))
+	}
+
+	pub fn is_replaying(&self) -> bool{
+		matches!(self.state.borrow().mode, Mode::Replaying(_))
+	}
+
+	/// index of the current frame of the recording or the replay
+	pub fn get_frame(&self) -> usize{
+		self.state.borrow().frame
+	}
+
+	pub fn get_seed(&self) -> Option<u64>{
+		match &self.state.borrow().mode{
+			Mode::Recording(recording) | Mode::Replaying(recording) => Some(recording.seed),
+			Mode::Idle => None,
+		}
+	}
+
+	/// delta time of the current frame of the replay
+	pub fn get_delta(&self) -> Option<Duration>{
+		let state = self.state.borrow();
+		match &state.mode{
+			Mode::Replaying(recording) => recording.frames.get(state.frame.checked_sub(1)?).map(|f| f.delta),
+			_ => None,
+		}
+	}
+
+	/// delta time of the replay or of tetra, use it instead of time::get_delta_time
+	/// for timers, tweens and the InputBuffer so the replay runs like the recording
+	pub fn get_frame_delta(&self, ctx: &Context) -> Duration{
+		self.get_delta().unwrap_or_else(|| time::get_delta_time(ctx))
+	}
+
+	/// updates the map from tetra, from the replay or records its bindings
+	pub fn update_actions(&self, ctx: &Context, actions: &mut ActionMap){
+		let mut state = self.state.borrow_mut();
+		let (gamepad, deadzone) = (actions.get_gamepad(), actions.get_deadzone());
+		let index = state.frame.saturating_sub(1);
+		match &mut state.mode{
+			Mode::Recording(recording) => {
+				if let Some(frame) = recording.frames.last_mut(){
+					actions.update_with(|binding| {
+						let value = binding.get_value(ctx, gamepad, deadzone);
+						if value > 0.0 && !frame.bindings.iter().any(|(b, _)| b == binding){
+							frame.bindings.push((*binding, value));
+						}
+						value
+					});
+				}else{
+					actions.update(ctx);
+				}
+			}
+			Mode::Replaying(recording) => {
+				let bindings = recording.frames.get(index).map(|f| f.bindings.as_slice()).unwrap_or(&[]);
+				actions.update_with(|binding| {
+					bindings.iter().find(|(b, _)| b == binding).map(|(_, v)| *v).unwrap_or(0.0)
+				});
+			}
+			Mode::Idle => actions.update(ctx),
+		}
+	}
+
+	/// returns false if the event of the player is ignored because of a replay
+	pub(crate) fn record_event(&self, event: &Event) -> bool{
+		let recorded = match RecordedEvent::from_event(event){
+			Some(recorded) => recorded,
+			None => return true,
+		};
+		let mut state = self.state.borrow_mut();
+		match state.mode{
+			Mode::Recording(_) => {
+				state.pending.push(recorded);
+				true
+			}
+			Mode::Replaying(_) => {
+				let pressed = matches!(recorded, RecordedEvent::KeyPressed(_) | RecordedEvent::MouseButtonPressed(_) | RecordedEvent::GamepadButtonPressed(..));
+				if state.stop_on_input && pressed{
+					state.mode = Mode::Idle;
+					debug!("replay stopped by input");
+				}
+				false
+			}
+			Mode::Idle => true,
+		}
+	}
+
+	/// starts the next frame, returns the events of the replay for this frame
+	pub(crate) fn begin_frame(&self, delta: Duration) -> Vec<Event>{
+		let mut state = self.state.borrow_mut();
+		let frame = state.frame;
+		if matches!(&state.mode, Mode::Replaying(recording) if frame >= recording.frames.len()){
+			debug!("replay finished");
+			state.mode = Mode::Idle;
+		}
+		let events = std::mem::take(&mut state.pending);
+		let result = match &mut state.mode{
+			Mode::Recording(recording) => {
+				recording.frames.push(RecordedFrame{
+					delta,
+					events,
+					bindings: Vec::new(),
+				});
+				Vec::new()
+			}
+			Mode::Replaying(recording) => recording.frames[frame].events.iter().map(|e| e.to_event()).collect(),
+			Mode::Idle => return Vec::new(),
+		};
+		state.frame += 1;
+		result
+	}
+}
+
+impl Default for InputRecorder {
+	fn default() -> InputRecorder {
+		InputRecorder::new()
+	}
+}
+
+impl std::fmt::Debug for InputRecorder {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		let state = self.state.borrow();
+		let mode = match state.mode{
+			Mode::Idle => "idle",
+			Mode::Recording(_) => "recording",
+			Mode::Replaying(_) => "replaying",
+		};
+		f.debug_struct("InputRecorder")
+			.field("mode", &mode)
+			.field("frame", &state.frame)
+			.finish()
+	}
+}
@@ -15,6 +15,8 @@ pub mod gui;
 pub mod utils;
 #[cfg(feature = "sound")]
 pub mod sound;
+#[cfg(feature = "sprite")]
+pub mod sprite;
 
 // experimental pack
 //mod experimental;
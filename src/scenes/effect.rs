@@ -0,0 +1,199 @@
+use std::time::Duration;
+use tetra::graphics::{self, Canvas, Color, DrawParams, Rectangle, Texture};
+use tetra::Context;
+use crate::animation::easing::Easing;
+use crate::TetraVec2;
+
+// height of the strips of the iris
+const IRIS_STRIP: f32 = 2.0;
+
+/// the direction in which the new scene moves in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlideDirection {
+	Left,
+	Right,
+	Up,
+	Down,
+}
+
+impl SlideDirection {
+	fn vector(self) -> TetraVec2{
+		match self{
+			SlideDirection::Left => TetraVec2::new(-1.0, 0.0),
+			SlideDirection::Right => TetraVec2::new(1.0, 0.0),
+			SlideDirection::Up => TetraVec2::new(0.0, -1.0),
+			SlideDirection::Down => TetraVec2::new(0.0, 1.0),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScreenEffect {
+	/// fades the old scene out to the color and the new scene in
+	FadeToColor(Color),
+	CrossFade,
+	/// the new scene pushes the old one out
+	Slide(SlideDirection),
+	/// the new scene is revealed by a moving edge
+	Wipe(SlideDirection),
+	/// the new scene is revealed by a growing circle
+	Iris,
+}
+
+/// animated change between two scenes, the input is blocked while it runs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScreenTransition {
+	pub effect: ScreenEffect,
+	pub duration: Duration,
+	pub easing: Easing,
+}
+
+#[allow(dead_code)]
+impl ScreenTransition {
+	pub fn new(effect: ScreenEffect, duration: Duration) -> ScreenTransition{
+		ScreenTransition{
+			effect,
+			duration,
+			easing: Easing::EaseInOut,
+		}
+	}
+
+	pub fn easing(mut self, easing: Easing) -> Self{
+		self.easing = easing;
+		self
+	}
+}
+
+/// canvases of the old and the new scene
+pub(crate) struct ScreenEffects {
+	from: Canvas,
+	to: Canvas,
+	pixel: Texture,
+	running: Option<(ScreenTransition, Duration)>,
+}
+
+impl ScreenEffects {
+	pub(crate) fn new(ctx: &mut Context, (width, height): (i32, i32)) -> tetra::Result<ScreenEffects>{
+		Ok(ScreenEffects{
+			from: Canvas::new(ctx, width, height)?,
+			to: Canvas::new(ctx, width, height)?,
+			pixel: Texture::from_rgba(ctx, 1, 1, &[255, 255, 255, 255])?,
+			running: None,
+		})
+	}
+
+	pub(crate) fn size(&self) -> (i32, i32){
+		self.from.size()
+	}
+
+	pub(crate) fn is_running(&self) -> bool{
+		self.running.is_some()
+	}
+
+	/// draws the old scene before the scene stack is changed
+	pub(crate) fn start<F>(&mut self, ctx: &mut Context, transition: ScreenTransition, draw_old: F) -> tetra::Result
+		where
+			F: FnOnce(&mut Context) -> tetra::Result,
+	{
+		graphics::set_canvas(ctx, &self.from);
+		graphics::clear(ctx, Color::BLACK);
+		let result = draw_old(ctx);
+		graphics::reset_canvas(ctx);
+		self.running = Some((transition, Duration::from_secs(0)));
+		result
+	}
+
+	pub(crate) fn tick(&mut self, delta: Duration){
+		if let Some((transition, elapsed)) = self.running.as_mut(){
+			*elapsed += delta;
+			if *elapsed >= transition.duration{
+				self.running = None;
+			}
+		}
+	}
+
+	/// draws the new scene and mixes it with the old one onto the target
+	pub(crate) fn draw<F>(&mut self, ctx: &mut Context, target: Option<&Canvas>, draw_new: F) -> tetra::Result
+		where
+			F: FnOnce(&mut Context) -> tetra::Result,
+	{
+		let (transition, elapsed) = match self.running{
+			Some(running) => running,
+			None => return Ok(()),
+		};
+		graphics::set_canvas(ctx, &self.to);
+		graphics::clear(ctx, Color::BLACK);
+		let result = draw_new(ctx);
+		match target{
+			Some(canvas) => graphics::set_canvas(ctx, canvas),
+			None => graphics::reset_canvas(ctx),
+		}
+		result?;
+		let progress = if transition.duration > Duration::from_secs(0){
+			transition.easing.ease(elapsed.as_secs_f32() / transition.duration.as_secs_f32())
+		}else{
+			1.0
+		};
+		self.compose(ctx, transition.effect, progress);
+		Ok(())
+	}
+
+	fn compose(&self, ctx: &mut Context, effect: ScreenEffect, progress: f32){
+		let (width, height) = self.size();
+		let size = TetraVec2::new(width as f32, height as f32);
+		match effect{
+			ScreenEffect::FadeToColor(color) => {
+				let (canvas, alpha) = if progress < 0.5{
+					(&self.from, progress * 2.0)
+				}else{
+					(&self.to, (1.0 - progress) * 2.0)
+				};
+				graphics::draw(ctx, canvas, TetraVec2::zero());
+				graphics::draw(ctx, &self.pixel, DrawParams::new()
+					.scale(size)
+					.color(Color::rgba(color.r, color.g, color.b, color.a * alpha)));
+			}
+			ScreenEffect::CrossFade => {
+				graphics::draw(ctx, &self.from, TetraVec2::zero());
+				graphics::draw(ctx, &self.to, DrawParams::new().color(Color::rgba(1.0, 1.0, 1.0, progress)));
+			}
+			ScreenEffect::Slide(direction) => {
+				let offset = direction.vector() * size;
+				graphics::draw(ctx, &self.from, offset * progress);
+				graphics::draw(ctx, &self.to, offset * (progress - 1.0));
+			}
+			ScreenEffect::Wipe(direction) => {
+				graphics::draw(ctx, &self.from, TetraVec2::zero());
+				let (w, h) = (size.x, size.y);
+				let clip = match direction{
+					SlideDirection::Right => Rectangle::new(0.0, 0.0, w * progress, h),
+					SlideDirection::Left => Rectangle::new(w * (1.0 - progress), 0.0, w * progress, h),
+					SlideDirection::Down => Rectangle::new(0.0, 0.0, w, h * progress),
+					SlideDirection::Up => Rectangle::new(0.0, h * (1.0 - progress), w, h * progress),
+				};
+				graphics::draw(ctx, &self.to, DrawParams::new()
+					.position(TetraVec2::new(clip.x, clip.y))
+					.clip(clip));
+			}
+			ScreenEffect::Iris => {
+				graphics::draw(ctx, &self.from, TetraVec2::zero());
+				// the circle is drawn as horizontal strips of the new scene
+				let center = size / 2.0;
+				let radius = center.magnitude() * progress;
+				let mut y = (center.y - radius).max(0.0).floor();
+				while y < (center.y + radius).min(size.y){
+					let dy = (y + IRIS_STRIP / 2.0 - center.y).abs();
+					if dy < radius{
+						let dx = (radius * radius - dy * dy).sqrt();
+						let x = (center.x - dx).max(0.0);
+						let clip = Rectangle::new(x, y, (center.x + dx).min(size.x) - x, IRIS_STRIP);
+						graphics::draw(ctx, &self.to, DrawParams::new()
+							.position(TetraVec2::new(clip.x, clip.y))
+							.clip(clip));
+					}
+					y += IRIS_STRIP;
+				}
+			}
+		}
+	}
+}
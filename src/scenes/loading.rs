@@ -0,0 +1,379 @@
+use std::any::Any;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tetra::graphics::{self, Color, DrawParams, Texture};
+use tetra::{Context, Event, TetraError, window};
+use log::{debug, error};
+use crate::scenes::{Scene, Transition};
+use crate::TetraVec2;
+
+type Payload = Box<dyn Any + Send>;
+type Work = Box<dyn FnOnce() -> Result<Payload, String> + Send>;
+type Upload<A> = Box<dyn FnOnce(&mut Context, &mut A, Payload) -> tetra::Result>;
+type Target<A, S> = Box<dyn FnOnce(&mut Context, &mut S, A) -> tetra::Result<Box<dyn Scene<S>>>>;
+
+struct Job<A> {
+	name: String,
+	work: Option<Work>,
+	upload: Option<Upload<A>>,
+}
+
+/// Jobs which fill the assets A, e.g. a struct with textures, sounds and tilemaps.
+/// The work of a job (file reads, parsing) runs on a background thread,
+/// the upload (textures, sounds) runs on the main thread and is spread over the frames.
+/// A job which fails is logged and skipped, the errors can be read with get_errors.
+pub struct LoadingQueue<A> {
+	assets: Option<A>,
+	jobs: Vec<Job<A>>,
+	receiver: Option<Receiver<(usize, Result<Payload, String>)>>,
+	ready: VecDeque<(usize, Payload)>,
+	read: usize,
+	done: usize,
+	errors: Vec<String>,
+	upload_budget: Duration,
+}
+
+#[allow(dead_code)]
+impl<A: 'static> LoadingQueue<A> {
+	pub fn new(assets: A) -> LoadingQueue<A>{
+		LoadingQueue{
+			assets: Some(assets),
+			jobs: Vec::new(),
+			receiver: None,
+			ready: VecDeque::new(),
+			read: 0,
+			done: 0,
+			errors: Vec::new(),
+			upload_budget: Duration::from_millis(8),
+		}
+	}
+
+	/// the result of the work is passed to the upload
+	pub fn job<T, W, U>(mut self, name: &str, work: W, upload: U) -> Self
+		where
+			T: Send + 'static,
+			W: FnOnce() -> Result<T, String> + Send + 'static,
+			U: FnOnce(&mut Context, &mut A, T) -> tetra::Result + 'static,
+	{
+		self.jobs.push(Job{
+			name: name.to_string(),
+			work: Some(Box::new(move || work().map(|value| Box::new(value) as Payload))),
+			upload: Some(Box::new(move |ctx, assets, payload| {
+				match payload.downcast::<T>(){
+					Ok(value) => upload(ctx, assets, *value),
+					Err(_) => Err(TetraError::PlatformError(format!("the work has not returned a {}", std::any::type_name::<T>()))),
+				}
+			})),
+		});
+		self
+	}
+
+	/// reads the file on the background thread
+	pub fn file<U>(self, path: &str, upload: U) -> Self
+		where
+			U: FnOnce(&mut Context, &mut A, Vec<u8>) -> tetra::Result + 'static,
+	{
+		let file = path.to_string();
+		self.job(path, move || std::fs::read(&file).map_err(|e| format!("{}: {}", file, e)), upload)
+	}
+
+	/// a job which only runs on the main thread
+	pub fn main_thread<U>(self, name: &str, upload: U) -> Self
+		where
+			U: FnOnce(&mut Context, &mut A) -> tetra::Result + 'static,
+	{
+		self.job(name, || Ok(()), move |ctx, assets, ()| upload(ctx, assets))
+	}
+
+	/// time per frame for the uploads, at least one upload runs per frame, default is 8ms
+	pub fn upload_budget(mut self, budget: Duration) -> Self{
+		self.upload_budget = budget;
+		self
+	}
+
+	pub fn len(&self) -> usize{
+		self.jobs.len()
+	}
+
+	pub fn is_empty(&self) -> bool{
+		self.jobs.is_empty()
+	}
+
+	pub fn is_started(&self) -> bool{
+		self.receiver.is_some()
+	}
+
+	pub fn is_finished(&self) -> bool{
+		self.done >= self.jobs.len()
+	}
+
+	/// 0.0 - 1.0, the work and the upload of a job count half each
+	pub fn get_progress(&self) -> f32{
+		if self.jobs.is_empty(){
+			return 1.0;
+		}
+		(self.read + self.done) as f32 / (self.jobs.len() * 2) as f32
+	}
+
+	pub fn get_errors(&self) -> &[String]{
+		&self.errors
+	}
+
+	/// the loaded assets, once the queue is finished
+	pub fn take_assets(&mut self) -> Option<A>{
+		if self.is_finished(){
+			self.assets.take()
+		}else{
+			None
+		}
+	}
+
+	/// starts the background thread, is called by the first update
+	pub fn start(&mut self){
+		if self.is_started(){
+			return;
+		}
+		let works: Vec<(usize, Work)> = self.jobs.iter_mut()
+			.enumerate()
+			.filter_map(|(i, job)| job.work.take().map(|work| (i, work)))
+			.collect();
+		let (sender, receiver) = mpsc::channel();
+		thread::spawn(move || {
+			for (i, work) in works{
+				if sender.send((i, work())).is_err(){
+					break;
+				}
+			}
+		});
+		self.receiver = Some(receiver);
+		debug!("loading {} jobs", self.jobs.len());
+	}
+
+	pub fn update(&mut self, ctx: &mut Context) -> tetra::Result{
+		self.start();
+		self.receive();
+		let start = Instant::now();
+		while let Some((i, payload)) = self.ready.pop_front(){
+			if let Some(upload) = self.jobs[i].upload.take(){
+				if let Some(assets) = self.assets.as_mut(){
+					if let Err(e) = upload(ctx, assets, payload){
+						self.fail(i, e.to_string());
+					}
+				}
+			}
+			self.done += 1;
+			if start.elapsed() >= self.upload_budget{
+				break;
+			}
+		}
+		Ok(())
+	}
+
+	fn receive(&mut self){
+		let receiver = match self.receiver.as_ref(){
+			Some(receiver) => receiver,
+			None => return,
+		};
+		let mut failed = Vec::new();
+		let mut disconnected = false;
+		loop{
+			match receiver.try_recv(){
+				Ok((i, Ok(payload))) => {
+					self.read += 1;
+					self.ready.push_back((i, payload));
+				}
+				Ok((i, Err(e))) => {
+					self.read += 1;
+					failed.push((i, e));
+				}
+				Err(TryRecvError::Empty) => break,
+				Err(TryRecvError::Disconnected) => {
+					disconnected = true;
+					break;
+				}
+			}
+		}
+		for (i, e) in failed{
+			self.fail(i, e);
+			self.done += 1;
+		}
+		// the thread has stopped early, the missing jobs can never finish
+		if disconnected && self.read < self.jobs.len(){
+			for i in 0..self.jobs.len(){
+				if self.jobs[i].upload.is_some() && !self.ready.iter().any(|(r, _)| *r == i){
+					self.fail(i, "the loading thread has stopped".to_string());
+					self.read += 1;
+					self.done += 1;
+				}
+			}
+		}
+	}
+
+	fn fail(&mut self, i: usize, e: String){
+		let job = &mut self.jobs[i];
+		job.upload = None;
+		error!("loading {} failed: {}", job.name, e);
+		self.errors.push(format!("{}: {}", job.name, e));
+	}
+}
+
+/// simple progress bar in the middle of the window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProgressBar {
+	pub size: TetraVec2,
+	pub background: Color,
+	pub border: Color,
+	pub fill: Color,
+}
+
+#[allow(dead_code)]
+impl ProgressBar {
+	pub fn new() -> ProgressBar{
+		ProgressBar{
+			size: TetraVec2::new(400.0, 24.0),
+			background: Color::BLACK,
+			border: Color::rgb(0.5, 0.5, 0.5),
+			fill: Color::WHITE,
+		}
+	}
+
+	pub fn size(mut self, width: f32, height: f32) -> Self{
+		self.size = TetraVec2::new(width, height);
+		self
+	}
+
+	pub fn colors(mut self, background: Color, border: Color, fill: Color) -> Self{
+		self.background = background;
+		self.border = border;
+		self.fill = fill;
+		self
+	}
+
+	/// draws only the bar, e.g. over the background of a loading screen
+	pub fn draw(&self, ctx: &mut Context, pixel: &Texture, progress: f32){
+		let (width, height) = window::get_size(ctx);
+		let position = (TetraVec2::new(width as f32, height as f32) - self.size) / 2.0;
+		graphics::draw(ctx, pixel, DrawParams::new()
+			.position(position - TetraVec2::one() * 2.0)
+			.scale(self.size + TetraVec2::one() * 4.0)
+			.color(self.border));
+		graphics::draw(ctx, pixel, DrawParams::new()
+			.position(position)
+			.scale(self.size)
+			.color(self.background));
+		graphics::draw(ctx, pixel, DrawParams::new()
+			.position(position)
+			.scale(TetraVec2::new(self.size.x * progress.clamp(0.0, 1.0), self.size.y))
+			.color(self.fill));
+	}
+}
+
+impl Default for ProgressBar {
+	fn default() -> ProgressBar {
+		ProgressBar::new()
+	}
+}
+
+/// Runs a LoadingQueue and replaces itself with the target scene when the queue is finished.
+/// The Replace is returned once, if a screen effect is running the SceneManager applies it after the effect.
+pub struct LoadingScene<A, S = ()> {
+	queue: LoadingQueue<A>,
+	target: Option<Target<A, S>>,
+	progress_bar: ProgressBar,
+	pixel: Texture,
+}
+
+#[allow(dead_code)]
+impl<A: 'static, S> LoadingScene<A, S> {
+	/// the target gets the loaded assets
+	pub fn new<F>(ctx: &mut Context, queue: LoadingQueue<A>, target: F) -> tetra::Result<LoadingScene<A, S>>
+		where
+			F: FnOnce(&mut Context, &mut S, A) -> tetra::Result<Box<dyn Scene<S>>> + 'static,
+	{
+		Ok(LoadingScene{
+			queue,
+			target: Some(Box::new(target)),
+			progress_bar: ProgressBar::new(),
+			pixel: Texture::from_rgba(ctx, 1, 1, &[255, 255, 255, 255])?,
+		})
+	}
+
+	pub fn progress_bar(mut self, progress_bar: ProgressBar) -> Self{
+		self.progress_bar = progress_bar;
+		self
+	}
+
+	pub fn get_progress(&self) -> f32{
+		self.queue.get_progress()
+	}
+
+	pub fn get_queue(&self) -> &LoadingQueue<A>{
+		&self.queue
+	}
+}
+
+impl<A: 'static, S> Scene<S> for LoadingScene<A, S> {
+	fn update(&mut self, ctx: &mut Context, state: &mut S) -> tetra::Result<Transition<S>> {
+		self.queue.update(ctx)?;
+		if let Some(assets) = self.queue.take_assets(){
+			if let Some(target) = self.target.take(){
+				return Ok(Transition::Replace(target(ctx, state, assets)?));
+			}
+		}
+		Ok(Transition::None)
+	}
+
+	fn draw(&mut self, ctx: &mut Context, _state: &mut S) -> tetra::Result {
+		graphics::clear(ctx, self.progress_bar.background);
+		self.progress_bar.draw(ctx, &self.pixel, self.queue.get_progress());
+		Ok(())
+	}
+
+	fn event(&mut self, _ctx: &mut Context, _state: &mut S, _event: Event) -> tetra::Result<Transition<S>> {
+		Ok(Transition::None)
+	}
+}
+
+#[cfg(all(test, feature = "animation"))]
+mod tests {
+	use crate::scenes::PendingTransition;
+	use super::*;
+
+	struct Empty;
+
+	impl Scene for Empty {
+		fn update(&mut self, _ctx: &mut Context, _state: &mut ()) -> tetra::Result<Transition>{
+			Ok(Transition::None)
+		}
+
+		fn draw(&mut self, _ctx: &mut Context, _state: &mut ()) -> tetra::Result{
+			Ok(())
+		}
+
+		fn event(&mut self, _ctx: &mut Context, _state: &mut (), _event: Event) -> tetra::Result<Transition>{
+			Ok(Transition::None)
+		}
+	}
+
+	// like LoadingScene::update, the target is taken when the queue is finished
+	fn finish(target: &mut Option<Box<dyn Scene>>) -> Transition{
+		match target.take(){
+			Some(scene) => Transition::Replace(scene),
+			None => Transition::None,
+		}
+	}
+
+	#[test]
+	fn finishes_after_the_push_effect(){
+		let mut target: Option<Box<dyn Scene>> = Some(Box::new(Empty));
+		let mut pending: PendingTransition<()> = PendingTransition::default();
+		// the queue finishes while the effect of the push still runs
+		for _ in 0..3{
+			assert!(matches!(pending.next(finish(&mut target), true), Transition::None));
+		}
+		assert!(target.is_none());
+		assert!(matches!(pending.next(finish(&mut target), false), Transition::Replace(_)));
+	}
+}
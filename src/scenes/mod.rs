@@ -1,100 +1,552 @@
-use tetra::{Context, Event, window, State, graphics};
+use std::time::Duration;
+use tetra::{Context, Event, window, State, graphics, time};
 use tetra::graphics::scaling::ScreenScaler;
 use crate::TetraVec2;
-use log::debug;
+use crate::utils::watcher::{AssetWatcher, AssetEvent};
+use crate::input_action::recording::InputRecorder;
+use std::collections::HashMap;
+use log::{debug, error};
 
+pub mod loading;
+#[cfg(feature = "animation")]
+pub mod effect;
 #[cfg(feature = "animation")]
 pub mod splash_screen;
 #[cfg(feature = "animation")]
 use crate::scenes::splash_screen::SplashScreenScene;
+#[cfg(feature = "animation")]
+use crate::scenes::effect::{ScreenEffects, ScreenTransition};
 
-pub trait Scene {
-	fn init(&mut self) -> tetra::Result;
-	fn save(&mut self) -> tetra::Result;
-	fn update(&mut self, ctx: &mut Context) -> tetra::Result<Transition>;
-	fn draw(&mut self, ctx: &mut Context) -> tetra::Result;
-	fn event(&mut self, ctx: &mut Context,event: Event) -> tetra::Result<Transition>;
+// a slow frame runs at most this many fixed updates, so it can catch up again
+const MAX_FIXED_UPDATES: u32 = 5;
+
+/// The lifecycle hooks are called by the SceneManager:
+/// on_enter when the scene is pushed, on_pause when another scene is pushed over it,
+/// on_resume when the scene above is popped and on_exit when the scene itself is popped.
+///
+/// A scene on top of the stack can be an overlay, e.g. a pause menu, a HUD or a dialog box:
+/// is_transparent draws the scenes below, update_below and event_below let them keep updating or receive the input.
+/// Only the transitions of the active scene are applied.
+///
+/// S is the shared state of the SceneManager, e.g. the config, the mixer, save data and fonts,
+/// it is passed to every method of the scene, see SceneManager::with_state.
+pub trait Scene<S = ()> {
+	/// is called whenever the scene becomes the active scene (after on_enter and on_resume)
+	fn init(&mut self, _state: &mut S) -> tetra::Result{
+		Ok(())
+	}
+	/// is called whenever the scene is no longer the active scene (before on_pause and on_exit)
+	fn save(&mut self, _state: &mut S) -> tetra::Result{
+		Ok(())
+	}
+	fn update(&mut self, ctx: &mut Context, state: &mut S) -> tetra::Result<Transition<S>>;
+	/// is called with a fixed time step if the SceneManager has one, see SceneManager::set_fixed_timestep
+	fn fixed_update(&mut self, _ctx: &mut Context, _state: &mut S) -> tetra::Result<Transition<S>>{
+		Ok(Transition::None)
+	}
+	fn draw(&mut self, ctx: &mut Context, state: &mut S) -> tetra::Result;
+	fn event(&mut self, ctx: &mut Context, state: &mut S, event: Event) -> tetra::Result<Transition<S>>;
+	fn on_enter(&mut self, _ctx: &mut Context, _state: &mut S) -> tetra::Result{
+		Ok(())
+	}
+	fn on_exit(&mut self, _ctx: &mut Context, _state: &mut S) -> tetra::Result{
+		Ok(())
+	}
+	fn on_pause(&mut self, _ctx: &mut Context, _state: &mut S) -> tetra::Result{
+		Ok(())
+	}
+	fn on_resume(&mut self, _ctx: &mut Context, _state: &mut S) -> tetra::Result{
+		Ok(())
+	}
+	/// a watched file has changed, see SceneManager::set_asset_watcher
+	fn reload(&mut self, _ctx: &mut Context, _state: &mut S, _event: &AssetEvent) -> tetra::Result{
+		Ok(())
+	}
+	/// the scene below is drawn first, default is opaque
+	fn is_transparent(&self) -> bool{
+		false
+	}
+	/// the scene below is updated too
+	fn update_below(&self) -> bool{
+		false
+	}
+	/// the events are passed to the scene below after this scene
+	fn event_below(&self) -> bool{
+		false
+	}
 }
 
 #[allow(dead_code)]
-pub enum Transition<'a> {
+pub enum Transition<S = ()> {
 	None,
-	Push(Box<dyn Scene>),
-	Load(&'a str),
+	Push(Box<dyn Scene<S>>),
+	/// pushes a scene of the registry, see SceneManager::register
+	PushNamed(String),
+	/// replaces the current scene with a scene of the registry
+	Load(String),
+	/// replaces the current scene
+	Replace(Box<dyn Scene<S>>),
 	Pop,
+	/// pops until the scene with the name is on top, nothing happens if it is already on top
+	PopTo(String),
+	/// removes all scenes below the current scene, they are saved and exited like with Pop
+	Clear,
 	Quit,
+	/// the transition with a screen effect instead of the default of the SceneManager
+	#[cfg(feature = "animation")]
+	Animated(Box<Transition<S>>, ScreenTransition),
+}
+
+#[allow(dead_code)]
+impl<S> Transition<S> {
+	pub fn push_named<N: Into<String>>(name: N) -> Transition<S>{
+		Transition::PushNamed(name.into())
+	}
+
+	pub fn load<N: Into<String>>(name: N) -> Transition<S>{
+		Transition::Load(name.into())
+	}
+
+	pub fn pop_to<N: Into<String>>(name: N) -> Transition<S>{
+		Transition::PopTo(name.into())
+	}
+
+	/// e.g. Transition::load("game").with_effect(ScreenTransition::new(ScreenEffect::Iris, Duration::from_secs(1)))
+	#[cfg(feature = "animation")]
+	pub fn with_effect(self, screen_transition: ScreenTransition) -> Transition<S>{
+		Transition::Animated(Box::new(self), screen_transition)
+	}
 }
 
-pub struct SceneManager {
-	scenes: Vec<Box<dyn Scene>>,
+type SceneFactory<S> = Box<dyn Fn(&mut Context, &mut S) -> tetra::Result<Box<dyn Scene<S>>>>;
+
+pub struct SceneManager<S = ()> {
+	scenes: Vec<Box<dyn Scene<S>>>,
+	// name of the scene if it was created by the registry
+	names: Vec<Option<String>>,
+	registry: HashMap<String, SceneFactory<S>>,
+	state: S,
 	screen_scaler: Option<ScreenScaler>,
+	asset_watcher: Option<AssetWatcher>,
+	fixed_timestep: Option<Duration>,
+	accumulator: Duration,
+	started: bool,
+	#[cfg(feature = "animation")]
+	screen_transition: Option<ScreenTransition>,
+	#[cfg(feature = "animation")]
+	effects: Option<ScreenEffects>,
+	#[cfg(feature = "animation")]
+	pending: PendingTransition<S>,
+	input_recorder: Option<InputRecorder>,
 }
 
 #[allow(dead_code)]
 impl SceneManager {
 	pub fn new(initial_scene: Box<dyn Scene>) -> SceneManager {
-		SceneManager {
-			scenes: vec![initial_scene],
-			screen_scaler: None
-		}
+		SceneManager::with_state((), initial_scene)
 	}
 	#[cfg(feature = "animation")]
 	pub fn new_with_splash_screen(ctx: &mut Context,initial_scene: Box<dyn Scene>) -> SceneManager {
+		SceneManager::with_state((), initial_scene).push_splash_screen(SplashScreenScene::new(ctx).unwrap())
+	}
+}
+
+#[allow(dead_code)]
+impl<S: 'static> SceneManager<S> {
+	/// the state is shared by all scenes and passed to the registry
+	pub fn with_state(state: S, initial_scene: Box<dyn Scene<S>>) -> SceneManager<S> {
 		SceneManager {
-			scenes: vec![initial_scene, Box::new(SplashScreenScene::new(ctx).unwrap())],
-			screen_scaler: None
+			scenes: vec![initial_scene],
+			names: vec![None],
+			registry: HashMap::new(),
+			state,
+			screen_scaler: None,
+			asset_watcher: None,
+			fixed_timestep: None,
+			accumulator: Duration::from_secs(0),
+			started: false,
+			#[cfg(feature = "animation")]
+			screen_transition: None,
+			#[cfg(feature = "animation")]
+			effects: None,
+			#[cfg(feature = "animation")]
+			pending: PendingTransition::default(),
+			input_recorder: None,
 		}
 	}
 
+	/// the splash screen is shown before the initial scene, e.g. SplashScreenScene::sequence().image(studio_logo).tetra_logo(ctx)?
+	#[cfg(feature = "animation")]
+	pub fn push_splash_screen(mut self, splash_screen: SplashScreenScene) -> Self{
+		self.scenes.push(Box::new(splash_screen));
+		self.names.push(None);
+		self
+	}
+
+	/// the factory creates the scene for Transition::Load and Transition::PushNamed
+	pub fn register<F>(mut self, name: &str, factory: F) -> Self
+		where
+			F: Fn(&mut Context, &mut S) -> tetra::Result<Box<dyn Scene<S>>> + 'static,
+	{
+		self.registry.insert(name.to_string(), Box::new(factory));
+		self
+	}
+
+	pub fn is_registered(&self, name: &str) -> bool{
+		self.registry.contains_key(name)
+	}
+
+	/// name of the active scene, if it was created by the registry
+	pub fn get_active_name(&self) -> Option<&str>{
+		self.names.last()?.as_deref()
+	}
+
+	pub fn get_state(&self) -> &S{
+		&self.state
+	}
+
+	pub fn get_state_mut(&mut self) -> &mut S{
+		&mut self.state
+	}
+
+	pub fn len(&self) -> usize{
+		self.scenes.len()
+	}
+
+	pub fn is_empty(&self) -> bool{
+		self.scenes.is_empty()
+	}
+
 	pub fn set_screen_scaler(mut self,screen_scaler: ScreenScaler) -> Self{
 		self.screen_scaler = Option::from(screen_scaler);
 		self
 	}
 
-	fn init_scene(&mut self) -> tetra::Result{
-		if let Some(active_scene) = self.scenes.last_mut() {
-			active_scene.init()?
+	/// changes of the watched files are passed to all scenes
+	pub fn set_asset_watcher(mut self, asset_watcher: AssetWatcher) -> Self{
+		self.asset_watcher = Option::from(asset_watcher);
+		self
+	}
+
+	pub fn get_asset_watcher_mut(&mut self) -> Option<&mut AssetWatcher>{
+		self.asset_watcher.as_mut()
+	}
+
+	/// Scene::fixed_update is called with this time step, e.g. Duration::from_secs_f64(1.0 / 60.0)
+	pub fn set_fixed_timestep(mut self, timestep: Duration) -> Self{
+		self.fixed_timestep = Option::from(timestep);
+		self
+	}
+
+	/// the events are recorded or replayed, see InputRecorder
+	pub fn set_input_recorder(mut self, input_recorder: InputRecorder) -> Self{
+		self.input_recorder = Option::from(input_recorder);
+		self
+	}
+
+	/// screen effect for every change of the active scene
+	#[cfg(feature = "animation")]
+	pub fn set_screen_transition(mut self, screen_transition: ScreenTransition) -> Self{
+		self.screen_transition = Option::from(screen_transition);
+		self
+	}
+
+	/// true while a screen effect runs, the input is blocked and the transitions of the scenes wait until it has finished
+	pub fn is_transitioning(&self) -> bool{
+		#[cfg(feature = "animation")]
+		{
+			if let Some(effects) = self.effects.as_ref(){
+				return effects.is_running();
+			}
+		}
+		false
+	}
+
+	fn reload_assets(&mut self, ctx: &mut Context) -> tetra::Result{
+		let events = match self.asset_watcher.as_mut(){
+			Some(watcher) => {
+				watcher.update(ctx);
+				watcher.events()
+			}
+			None => return Ok(()),
+		};
+		for event in events.iter(){
+			for scene in self.scenes.iter_mut(){
+				scene.reload(ctx, &mut self.state, event)?;
+			}
 		}
 		Ok(())
 	}
-}
 
-impl State for SceneManager {
-	fn update(&mut self, ctx: &mut Context) -> tetra::Result {
-		match self.scenes.last_mut() {
-			Some(active_scene) => match active_scene.update(ctx)? {
-				Transition::None => {}
-				Transition::Load(s) =>{
-					debug!("load scene {}", s);
+	// the scenes which were created with the manager are entered with the first update
+	fn start(&mut self, ctx: &mut Context) -> tetra::Result{
+		self.started = true;
+		let count = self.scenes.len();
+		for (i, scene) in self.scenes.iter_mut().enumerate(){
+			scene.on_enter(ctx, &mut self.state)?;
+			if i + 1 < count{
+				scene.on_pause(ctx, &mut self.state)?;
+			}
+		}
+		self.init_scene()
+	}
+
+	fn fixed_update(&mut self, ctx: &mut Context, delta: Duration) -> tetra::Result{
+		let timestep = match self.fixed_timestep{
+			Some(timestep) if timestep > Duration::from_secs(0) => timestep,
+			_ => return Ok(()),
+		};
+		self.accumulator += delta;
+		let mut updates = 0;
+		while self.accumulator >= timestep{
+			self.accumulator -= timestep;
+			updates += 1;
+			if updates > MAX_FIXED_UPDATES{
+				self.accumulator = Duration::from_secs(0);
+				break;
+			}
+			let mut transition = Transition::None;
+			let count = self.scenes.len();
+			let first = lowest_scene(&self.scenes, |s| s.update_below());
+			for (i, scene) in self.scenes.iter_mut().enumerate().skip(first){
+				let scene_transition = scene.fixed_update(ctx, &mut self.state)?;
+				if i + 1 == count{
+					transition = scene_transition;
+				}
+			}
+			if !matches!(transition, Transition::None){
+				self.apply(ctx, transition)?;
+				break;
+			}
+		}
+		Ok(())
+	}
+
+	#[cfg(feature = "animation")]
+	fn apply(&mut self, ctx: &mut Context, transition: Transition<S>) -> tetra::Result{
+		// the scenes are still updated during the effect, their transition waits until it has finished
+		let transition = self.pending.next(transition, self.is_transitioning());
+		let (transition, screen_transition) = match transition{
+			Transition::Animated(transition, screen_transition) => (*transition, Some(screen_transition)),
+			transition => (transition, self.screen_transition),
+		};
+		if let Some(screen_transition) = screen_transition{
+			if !matches!(transition, Transition::None | Transition::Quit){
+				self.start_effect(ctx, screen_transition)?;
+			}
+		}
+		self.change(ctx, transition)
+	}
+
+	#[cfg(not(feature = "animation"))]
+	fn apply(&mut self, ctx: &mut Context, transition: Transition<S>) -> tetra::Result{
+		self.change(ctx, transition)
+	}
+
+	// the old scenes are drawn to a canvas before they are changed
+	#[cfg(feature = "animation")]
+	fn start_effect(&mut self, ctx: &mut Context, screen_transition: ScreenTransition) -> tetra::Result{
+		let size = match self.screen_scaler.as_ref(){
+			Some(scaler) => scaler.canvas().size(),
+			None => window::get_size(ctx),
+		};
+		if self.effects.as_ref().map(|e| e.size()) != Some(size){
+			self.effects = Some(ScreenEffects::new(ctx, size)?);
+		}
+		let scenes = &mut self.scenes;
+		let state = &mut self.state;
+		match self.effects.as_mut(){
+			Some(effects) => effects.start(ctx, screen_transition, |ctx| draw_scenes(ctx, state, scenes)),
+			None => Ok(()),
+		}
+	}
+
+	fn change(&mut self, ctx: &mut Context, transition: Transition<S>) -> tetra::Result{
+		match transition{
+			Transition::None => {}
+			Transition::Push(scene) => {
+				self.push_scene(ctx, None, scene)?;
+			}
+			Transition::PushNamed(name) => {
+				if let Some(scene) = self.create(ctx, &name)?{
+					self.push_scene(ctx, Some(name), scene)?;
 				}
-				Transition::Push(s) => {
-					active_scene.save()?;
-					self.scenes.push(s);
-					self.init_scene()?;
+			}
+			Transition::Load(name) => {
+				if let Some(scene) = self.create(ctx, &name)?{
+					self.replace_scene(ctx, Some(name), scene)?;
+				}
+			}
+			Transition::Replace(scene) => {
+				self.replace_scene(ctx, None, scene)?;
+			}
+			Transition::Pop => {
+				self.pop_scene(ctx)?;
+				self.resume_scene(ctx)?;
+			}
+			Transition::PopTo(name) => {
+				if self.get_active_name() == Some(name.as_str()){
+					// already active, nothing to resume
+				}else if self.names.iter().any(|n| n.as_deref() == Some(name.as_str())){
+					while self.get_active_name() != Some(name.as_str()){
+						self.pop_scene(ctx)?;
+					}
+					self.resume_scene(ctx)?;
+				}else{
+					error!("scene {} is not on the stack!", name);
 				}
-				Transition::Pop => {
-					active_scene.save()?;
-					self.scenes.pop();
-					self.init_scene()?;
+			}
+			Transition::Clear => {
+				let count = self.scenes.len().saturating_sub(1);
+				self.names.drain(..count);
+				let below: Vec<Box<dyn Scene<S>>> = self.scenes.drain(..count).collect();
+				for scene in below.into_iter().rev(){
+					self.exit_scene(ctx, scene)?;
 				}
-				Transition::Quit => {
-					window::quit(ctx)
+			}
+			Transition::Quit => {
+				while !self.scenes.is_empty(){
+					self.pop_scene(ctx)?;
 				}
-			},
-			None => window::quit(ctx),
+				window::quit(ctx)
+			}
+			#[cfg(feature = "animation")]
+			Transition::Animated(transition, _) => {
+				self.change(ctx, *transition)?;
+			}
+		}
+		Ok(())
+	}
+
+	fn create(&mut self, ctx: &mut Context, name: &str) -> tetra::Result<Option<Box<dyn Scene<S>>>>{
+		match self.registry.get(name){
+			Some(factory) => {
+				debug!("load scene {}", name);
+				Ok(Some(factory(ctx, &mut self.state)?))
+			}
+			None => {
+				error!("scene {} is not registered!", name);
+				Ok(None)
+			}
+		}
+	}
+
+	fn push_scene(&mut self, ctx: &mut Context, name: Option<String>, scene: Box<dyn Scene<S>>) -> tetra::Result{
+		if let Some(active_scene) = self.scenes.last_mut(){
+			active_scene.save(&mut self.state)?;
+			active_scene.on_pause(ctx, &mut self.state)?;
+		}
+		self.enter_scene(ctx, name, scene)
+	}
+
+	fn replace_scene(&mut self, ctx: &mut Context, name: Option<String>, scene: Box<dyn Scene<S>>) -> tetra::Result{
+		self.pop_scene(ctx)?;
+		self.enter_scene(ctx, name, scene)
+	}
+
+	fn enter_scene(&mut self, ctx: &mut Context, name: Option<String>, mut scene: Box<dyn Scene<S>>) -> tetra::Result{
+		scene.on_enter(ctx, &mut self.state)?;
+		self.scenes.push(scene);
+		self.names.push(name);
+		self.init_scene()
+	}
+
+	fn pop_scene(&mut self, ctx: &mut Context) -> tetra::Result{
+		self.names.pop();
+		match self.scenes.pop(){
+			Some(scene) => self.exit_scene(ctx, scene),
+			None => Ok(()),
+		}
+	}
+
+	fn exit_scene(&mut self, ctx: &mut Context, mut scene: Box<dyn Scene<S>>) -> tetra::Result{
+		scene.save(&mut self.state)?;
+		scene.on_exit(ctx, &mut self.state)
+	}
+
+	fn resume_scene(&mut self, ctx: &mut Context) -> tetra::Result{
+		if let Some(active_scene) = self.scenes.last_mut(){
+			active_scene.on_resume(ctx, &mut self.state)?;
+		}
+		self.init_scene()
+	}
+
+	fn dispatch_event(&mut self, ctx: &mut Context, event: Event) -> tetra::Result{
+		if self.is_transitioning(){
+			return Ok(());
 		}
+		let mut transition = Transition::Quit;
+		let count = self.scenes.len();
+		let first = lowest_scene(&self.scenes, |s| s.event_below());
+		// the active scene gets the event first
+		for (i, scene) in self.scenes.iter_mut().enumerate().skip(first).rev(){
+			let scene_transition = scene.event(ctx, &mut self.state, event.clone())?;
+			if i + 1 == count{
+				transition = scene_transition;
+			}
+		}
+		self.apply(ctx, transition)
+	}
 
+	fn init_scene(&mut self) -> tetra::Result{
+		if let Some(active_scene) = self.scenes.last_mut() {
+			active_scene.init(&mut self.state)?
+		}
 		Ok(())
 	}
+}
+
+impl<S: 'static> State for SceneManager<S> {
+	fn update(&mut self, ctx: &mut Context) -> tetra::Result {
+		if !self.started{
+			self.start(ctx)?;
+		}
+		// while replaying the frame runs with the delta of the recording
+		let mut delta = time::get_delta_time(ctx);
+		if let Some(recorder) = self.input_recorder.clone(){
+			for event in recorder.begin_frame(delta){
+				self.dispatch_event(ctx, event)?;
+			}
+			delta = recorder.get_delta().unwrap_or(delta);
+		}
+		#[cfg(feature = "animation")]
+		{
+			if let Some(effects) = self.effects.as_mut(){
+				effects.tick(delta);
+			}
+		}
+		self.reload_assets(ctx)?;
+		self.fixed_update(ctx, delta)?;
+		let mut transition = Transition::Quit;
+		let count = self.scenes.len();
+		let first = lowest_scene(&self.scenes, |s| s.update_below());
+		for (i, scene) in self.scenes.iter_mut().enumerate().skip(first){
+			let scene_transition = scene.update(ctx, &mut self.state)?;
+			if i + 1 == count{
+				transition = scene_transition;
+			}
+		}
+		self.apply(ctx, transition)
+	}
 
 	fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
 		if let Some(scaler) = self.screen_scaler.as_ref() {
 			graphics::set_canvas(ctx, scaler.canvas())
 		}
 
-		if let Some(active_scene) = self.scenes.last_mut() {
-			active_scene.draw(ctx)?
+		#[cfg(feature = "animation")]
+		{
+			if let Some(effects) = self.effects.as_mut().filter(|e| e.is_running()){
+				let target = self.screen_scaler.as_ref().map(|s| s.canvas());
+				let scenes = &mut self.scenes;
+				let state = &mut self.state;
+				effects.draw(ctx, target, |ctx| draw_scenes(ctx, state, scenes))?;
+			}else{
+				draw_scenes(ctx, &mut self.state, &mut self.scenes)?;
+			}
 		}
+		#[cfg(not(feature = "animation"))]
+		draw_scenes(ctx, &mut self.state, &mut self.scenes)?;
 
 		if let Some(scaler) = self.screen_scaler.as_ref() {
 			graphics::reset_canvas(ctx);
@@ -110,28 +562,122 @@ impl State for SceneManager {
 				scaler.set_outer_size(width, height)
 			}
 		}
-		match self.scenes.last_mut() {
-			Some(active_scene) => match active_scene.event(ctx, event)?{
-				Transition::None => {}
-				Transition::Load(s) =>{
-					debug!("load scene {}", s);
-				}
-				Transition::Push(s) => {
-					self.scenes.push(s);
-					self.init_scene()?;
-				}
-				Transition::Pop => {
-					self.scenes.pop();
-					self.init_scene()?;
-				}
-				Transition::Quit => {
-					window::quit(ctx)
+		if let Some(recorder) = self.input_recorder.as_ref(){
+			if !self.is_transitioning() && !recorder.record_event(&event){
+				return Ok(());
+			}
+		}
+		self.dispatch_event(ctx, event)
+	}
+}
+
+// the latest transition which was returned while a screen effect was running
+#[cfg(feature = "animation")]
+struct PendingTransition<S> {
+	transition: Option<Transition<S>>,
+}
+
+#[cfg(feature = "animation")]
+impl<S> PendingTransition<S> {
+	// the transition to apply now, Quit is never delayed
+	fn next(&mut self, transition: Transition<S>, running: bool) -> Transition<S>{
+		match transition{
+			Transition::None => {
+				if running{
+					Transition::None
+				}else{
+					self.transition.take().unwrap_or(Transition::None)
 				}
-			},
-			None => window::quit(ctx),
+			}
+			Transition::Quit => {
+				self.transition = None;
+				Transition::Quit
+			}
+			transition if running => {
+				debug!("transition waits for the screen effect");
+				self.transition = Some(transition);
+				Transition::None
+			}
+			transition => {
+				self.transition = None;
+				transition
+			}
 		}
-		Ok(())
 	}
 }
 
+#[cfg(feature = "animation")]
+impl<S> Default for PendingTransition<S> {
+	fn default() -> PendingTransition<S> {
+		PendingTransition{
+			transition: None,
+		}
+	}
+}
+
+// draws from the lowest visible scene upward
+fn draw_scenes<S>(ctx: &mut Context, state: &mut S, scenes: &mut [Box<dyn Scene<S>>]) -> tetra::Result{
+	let first = lowest_scene(scenes, |s| s.is_transparent());
+	for scene in scenes.iter_mut().skip(first){
+		scene.draw(ctx, state)?;
+	}
+	Ok(())
+}
+
+// index of the lowest scene which is reached from the active scene while the scenes above pass it down
+fn lowest_scene<S, F>(scenes: &[Box<dyn Scene<S>>], pass_below: F) -> usize
+	where
+		F: Fn(&dyn Scene<S>) -> bool,
+{
+	let mut first = scenes.len().saturating_sub(1);
+	while first > 0 && pass_below(scenes[first].as_ref()){
+		first -= 1;
+	}
+	first
+}
+
+#[cfg(all(test, feature = "animation"))]
+mod tests {
+	use super::*;
+
+	struct Empty;
 
+	impl Scene for Empty {
+		fn update(&mut self, _ctx: &mut Context, _state: &mut ()) -> tetra::Result<Transition>{
+			Ok(Transition::None)
+		}
+
+		fn draw(&mut self, _ctx: &mut Context, _state: &mut ()) -> tetra::Result{
+			Ok(())
+		}
+
+		fn event(&mut self, _ctx: &mut Context, _state: &mut (), _event: Event) -> tetra::Result<Transition>{
+			Ok(Transition::None)
+		}
+	}
+
+	#[test]
+	fn replace_waits_for_the_effect(){
+		let mut pending: PendingTransition<()> = PendingTransition::default();
+		assert!(matches!(pending.next(Transition::Replace(Box::new(Empty)), true), Transition::None));
+		assert!(matches!(pending.next(Transition::None, true), Transition::None));
+		assert!(matches!(pending.next(Transition::None, false), Transition::Replace(_)));
+		assert!(matches!(pending.next(Transition::None, false), Transition::None));
+	}
+
+	#[test]
+	fn latest_transition_wins(){
+		let mut pending: PendingTransition<()> = PendingTransition::default();
+		pending.next(Transition::Replace(Box::new(Empty)), true);
+		pending.next(Transition::Pop, true);
+		assert!(matches!(pending.next(Transition::None, false), Transition::Pop));
+	}
+
+	#[test]
+	fn quit_is_not_delayed(){
+		let mut pending: PendingTransition<()> = PendingTransition::default();
+		pending.next(Transition::Pop, true);
+		assert!(matches!(pending.next(Transition::Quit, true), Transition::Quit));
+		assert!(matches!(pending.next(Transition::None, false), Transition::None));
+	}
+}
@@ -1,123 +1,395 @@
+use std::time::Duration;
 use tetra::{
     graphics::{self, DrawParams,Color},
     math::Vec2,
-    Context, Event,
+    Context, Event, window,
 };
 use tetra::graphics::{Texture, Rectangle};
+#[cfg(feature = "sound")]
+use tetra::audio::{Sound, SoundInstance};
 use crate::input_action;
 use crate::scenes::{Scene, Transition};
 use keyframe::Keyframe;
-use keyframe::functions::{EaseInOutQuint, EaseInOut, Linear};
+use keyframe::functions::{EaseInOutQuint, Linear};
 use crate::utils::timer::Timer;
-use crate::animation::Tween;
+use crate::animation::clip::{AnimationClip, PlaybackMode};
+use crate::animation::values::TweenVec2;
 
+// the tetra logo is centered around this point of the atlas layout
+const TETRA_LOGO_CENTER: (f32, f32) = (362.0, 256.0);
+const TETRA_LOGO_DURATION: Duration = Duration::from_secs(5);
+
+/// what a key or button does while the splash screen is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipMode {
+    Never,
+    /// skips the current image
+    Image,
+    /// skips the whole sequence
+    All,
+}
+
+/// one image of the splash sequence, e.g. the logo of the studio
+#[derive(Debug, Clone)]
+pub struct SplashImage {
+    texture: Texture,
+    clip: Option<Rectangle>,
+    position: Vec2<f32>,
+    scale: Vec2<f32>,
+    background: Color,
+    duration: Duration,
+    fade_in: Duration,
+    fade_out: Duration,
+    #[cfg(feature = "sound")]
+    sound: Option<Sound>,
+}
 
+#[allow(dead_code)]
+impl SplashImage {
+    pub fn new(texture: Texture, duration: Duration) -> SplashImage {
+        SplashImage {
+            texture,
+            clip: None,
+            position: Vec2::new(0.5, 0.5),
+            scale: Vec2::one(),
+            background: Color::BLACK,
+            duration,
+            fade_in: Duration::from_millis(500),
+            fade_out: Duration::from_millis(500),
+            #[cfg(feature = "sound")]
+            sound: None,
+        }
+    }
+
+    /// the animated tetra logo, add it with SplashScreenScene::tetra(),
+    /// duration (5s), fades, background, position, scale and sound can be changed like for every image
+    pub fn tetra_logo(ctx: &mut Context) -> tetra::Result<SplashImage> {
+        let atlas = Texture::from_file_data(ctx, include_bytes!("../../resources/splashScreen/atlas.png"))?;
+        Ok(SplashImage::new(atlas, TETRA_LOGO_DURATION))
+    }
+
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn clip(mut self, clip: Rectangle) -> Self {
+        self.clip = Some(clip);
+        self
+    }
+
+    /// center of the image relative to the screen size, default is the middle (0.5, 0.5)
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Vec2::new(x, y);
+        self
+    }
+
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.scale = Vec2::new(scale, scale);
+        self
+    }
+
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// default is 500ms each
+    pub fn fade(mut self, fade_in: Duration, fade_out: Duration) -> Self {
+        self.fade_in = fade_in;
+        self.fade_out = fade_out;
+        self
+    }
+
+    /// is played when the image appears
+    #[cfg(feature = "sound")]
+    pub fn sound(mut self, sound: Sound) -> Self {
+        self.sound = Some(sound);
+        self
+    }
+
+    fn draw(&self, ctx: &mut Context, screen: Vec2<f32>, alpha: f32) {
+        graphics::clear(ctx, self.background);
+        let (width, height) = match self.clip {
+            Some(clip) => (clip.width, clip.height),
+            None => (self.texture.width() as f32, self.texture.height() as f32),
+        };
+        graphics::draw(ctx, &self.texture, DrawParams{
+            position: screen * self.position,
+            clip: self.clip,
+            origin: Vec2::new(width, height) / 2.0,
+            scale: self.scale,
+            color: Color::rgba(1.0, 1.0, 1.0, alpha),
+            ..Default::default()
+        });
+    }
+}
+
+enum SplashStep {
+    Image(SplashImage),
+    Tetra(SplashImage, Box<AnimationClip>),
+}
+
+impl SplashStep {
+    fn image(&self) -> &SplashImage {
+        match self {
+            SplashStep::Image(image) | SplashStep::Tetra(image, _) => image,
+        }
+    }
+}
+
+/// Shows the images of the sequence one after the other and pops itself at the end,
+/// new() shows only the tetra logo.
 #[allow(dead_code)]
 pub struct SplashScreenScene {
-    atlas: Texture,
+    steps: Vec<SplashStep>,
+    current: usize,
     timer: Timer,
-    animations: Vec<Tween>,
+    started: bool,
+    skip: SkipMode,
+    size: Option<Vec2<f32>>,
+    #[cfg(feature = "sound")]
+    sound: Option<SoundInstance>,
 }
 
+#[allow(dead_code)]
 impl SplashScreenScene {
     pub fn new(ctx: &mut Context) -> tetra::Result<SplashScreenScene> {
-        let timer = Timer::new_sec(5);
-        let tween1 = Tween::from_keyframes(vec![
-            Keyframe::new(0.0,0.0,EaseInOutQuint),
-            Keyframe::new(5.0,0.5,EaseInOutQuint),
-            Keyframe::new(0.0,1.0,EaseInOut)], 0,4,true);
-        let tween2 = Tween::from_keyframes(vec![
-            Keyframe::new(-0.1,0.0,EaseInOutQuint),
-            Keyframe::new(0.1,0.5,EaseInOutQuint),
-            Keyframe::new(-0.1,1.0,EaseInOut)],0,4,true);
-        let tween3 = Tween::from_keyframes(vec![
-            Keyframe::new(0.0,0.0,Linear),
-            Keyframe::new(6.283_185_5,1.0,Linear)],0,10,true);
-        let tween = vec![tween1, tween2, tween3];
-        Ok(SplashScreenScene {
-            atlas: Texture::from_file_data(ctx, include_bytes!("../../resources/splashScreen/atlas.png"))?,
-            timer,
-            animations: tween,
-        })
+        SplashScreenScene::sequence().tetra_logo(ctx)
     }
-}
 
-impl Scene for SplashScreenScene {
-    fn init(&mut self)-> tetra::Result{
-        Ok(())
+    /// an empty sequence, add the images with image() and tetra_logo()
+    pub fn sequence() -> SplashScreenScene {
+        SplashScreenScene {
+            steps: Vec::new(),
+            current: 0,
+            timer: Timer::new(0),
+            started: false,
+            skip: SkipMode::Image,
+            size: None,
+            #[cfg(feature = "sound")]
+            sound: None,
+        }
+    }
+
+    pub fn image(mut self, image: SplashImage) -> Self {
+        self.steps.push(SplashStep::Image(image));
+        self
+    }
+
+    /// the tetra logo with the default settings, see SplashImage::tetra_logo
+    pub fn tetra_logo(self, ctx: &mut Context) -> tetra::Result<Self> {
+        Ok(self.tetra(SplashImage::tetra_logo(ctx)?))
+    }
+
+    /// e.g. tetra(SplashImage::tetra_logo(ctx)?.duration(Duration::from_secs(3)).background(Color::WHITE))
+    pub fn tetra(mut self, image: SplashImage) -> Self {
+        self.steps.push(SplashStep::Tetra(image, Box::new(create_animation())));
+        self
     }
 
-    fn save(&mut self)-> tetra::Result{
+    /// default is SkipMode::Image
+    pub fn skip(mut self, skip: SkipMode) -> Self {
+        self.skip = skip;
+        self
+    }
+
+    /// size of the screen for the positions, e.g. the inner size of the ScreenScaler, default is the window size
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Some(Vec2::new(width, height));
+        self
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    fn start_step(&mut self, _ctx: &mut Context) -> tetra::Result {
+        self.started = true;
+        if let Some(step) = self.steps.get(self.current) {
+            self.timer = Timer::from_duration(step.image().duration);
+            #[cfg(feature = "sound")]
+            {
+                if let Some(sound) = step.image().sound.as_ref() {
+                    self.sound = Some(sound.play(_ctx)?);
+                }
+            }
+        }
         Ok(())
     }
 
-    fn update(&mut self, _ctx: &mut Context) -> tetra::Result<Transition> {
-        self.animations[0].update();
-        self.animations[1].update();
-        self.animations[2].update();
-        Ok(Transition::None)
+    fn next_step(&mut self) {
+        #[cfg(feature = "sound")]
+        {
+            if let Some(sound) = self.sound.take() {
+                sound.stop();
+            }
+        }
+        self.current += 1;
+        self.started = false;
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> tetra::Result {
-        graphics::clear(ctx, Color::BLACK);
-        let logo_pos = Vec2::new(120.0, 0.0);
-        let gear_pos = logo_pos + Vec2::new(256.0, 256.0);
-        let text_pos = logo_pos + Vec2::new(310.0, 250.0);
-        let alien_pos = logo_pos + Vec2::new(500.0, 224.0) + Vec2::new(self.animations[0].value(), self.animations[0].value()*2.5);
-        graphics::draw(ctx, &self.atlas, DrawParams{
-            position: alien_pos,
-            clip: get_clip("tetra_alien_body"),
-            ..Default::default()
-        });
-        graphics::draw(ctx, &self.atlas, DrawParams{
-            position: alien_pos+Vec2::new(0.0,12.0),
-            clip: get_clip("tetra_alien_eye1"),
-            origin: Vec2::new(0.0,48.0),
-            rotation: self.animations[1].value()*-1.0,
-            ..Default::default()
-        });
-        graphics::draw(ctx, &self.atlas, DrawParams{
-            position: text_pos+Vec2::new(3.0,-32.0),
-            clip: get_clip("tetra_text1"),
-            ..Default::default()
-        });
-        graphics::draw(ctx, &self.atlas, DrawParams{
-            position: text_pos+Vec2::new(-8.0,20.0),
-            clip: get_clip("tetra_text2"),
-            ..Default::default()
-        });
-        graphics::draw(ctx, &self.atlas, DrawParams{
-            position: alien_pos+Vec2::new(10.0,30.0),
-            clip: get_clip("tetra_alien_eye2"),
-            origin: Vec2::new(0.0,26.0),
-            rotation: self.animations[1].value(),
-            ..Default::default()
-        });
-        graphics::draw(ctx, &self.atlas, DrawParams{
-            position: gear_pos,
-            clip: get_clip("tetra_gear_wheel"),
-            origin: get_origin("tetra_gear_wheel").unwrap_or_default(),
-            rotation: self.animations[2].value(),
-            ..Default::default()
-        });
-        graphics::draw(ctx, &self.atlas, DrawParams{
-            position: gear_pos,
-            clip: get_clip("tetra_symbol"),
-            origin: get_origin("tetra_symbol").unwrap_or_default(),
-            ..Default::default()
-        });
+    fn alpha(&self) -> f32 {
+        let (fade_in, fade_out) = match self.steps.get(self.current) {
+            Some(step) => (step.image().fade_in, step.image().fade_out),
+            None => return 0.0,
+        };
+        let elapsed = self.timer.elapsed();
+        let remaining = self.timer.duration().checked_sub(elapsed).unwrap_or_default();
+        if elapsed < fade_in {
+            elapsed.as_secs_f32() / fade_in.as_secs_f32()
+        } else if remaining < fade_out {
+            remaining.as_secs_f32() / fade_out.as_secs_f32()
+        } else {
+            1.0
+        }
+    }
+}
+
+impl<S> Scene<S> for SplashScreenScene {
+    fn update(&mut self, ctx: &mut Context, _state: &mut S) -> tetra::Result<Transition<S>> {
+        if !self.started {
+            self.start_step(ctx)?;
+        }
+        self.timer.update(ctx);
+        if let Some(SplashStep::Tetra(_, animation)) = self.steps.get_mut(self.current) {
+            animation.advance(ctx);
+        }
+        if self.timer.finished() {
+            self.next_step();
+        }
+        if self.is_finished() {
+            Ok(Transition::Pop)
+        } else {
+            Ok(Transition::None)
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context, _state: &mut S) -> tetra::Result {
+        let screen = match self.size {
+            Some(size) => size,
+            None => {
+                let (width, height) = window::get_size(ctx);
+                Vec2::new(width as f32, height as f32)
+            }
+        };
+        let alpha = self.alpha();
+        match self.steps.get(self.current) {
+            Some(SplashStep::Image(image)) => image.draw(ctx, screen, alpha),
+            Some(SplashStep::Tetra(image, animation)) => draw_tetra_logo(ctx, image, animation, screen, alpha),
+            None => graphics::clear(ctx, Color::BLACK),
+        }
         Ok(())
     }
 
-    fn event(&mut self, _ctx: &mut Context, event: Event) -> tetra::Result<Transition> {
-        if self.timer.finished() || input_action::is_any_key(&event){
+    fn event(&mut self, _ctx: &mut Context, _state: &mut S, event: Event) -> tetra::Result<Transition<S>> {
+        if input_action::is_any_key(&event) {
+            match self.skip {
+                SkipMode::Never => {}
+                SkipMode::Image => self.next_step(),
+                SkipMode::All => {
+                    while !self.is_finished() {
+                        self.next_step();
+                    }
+                }
+            }
+        }
+        if self.is_finished() {
             Ok(Transition::Pop)
-        }else{
+        } else {
             Ok(Transition::None)
         }
     }
 }
 
+fn draw_tetra_logo(ctx: &mut Context, image: &SplashImage, animation: &AnimationClip, screen: Vec2<f32>, alpha: f32) {
+    graphics::clear(ctx, image.background);
+    let atlas = &image.texture;
+    let color = Color::rgba(1.0, 1.0, 1.0, alpha);
+    let scale = image.scale;
+    // the parts are placed relative to the center of the logo
+    let center = screen * image.position;
+    let logo_pos = -Vec2::new(TETRA_LOGO_CENTER.0, TETRA_LOGO_CENTER.1);
+    let gear_pos = logo_pos + Vec2::new(256.0, 256.0);
+    let text_pos = logo_pos + Vec2::new(310.0, 250.0);
+    let alien_pos = logo_pos + Vec2::new(500.0, 224.0) + animation.get_position();
+    graphics::draw(ctx, atlas, DrawParams{
+        position: center + alien_pos * scale,
+        scale,
+        clip: get_clip("tetra_alien_body"),
+        color,
+        ..Default::default()
+    });
+    graphics::draw(ctx, atlas, DrawParams{
+        position: center + (alien_pos+Vec2::new(0.0,12.0)) * scale,
+        scale,
+        clip: get_clip("tetra_alien_eye1"),
+        origin: Vec2::new(0.0,48.0),
+        rotation: animation.get_value("eye")*-1.0,
+        color,
+    });
+    graphics::draw(ctx, atlas, DrawParams{
+        position: center + (text_pos+Vec2::new(3.0,-32.0)) * scale,
+        scale,
+        clip: get_clip("tetra_text1"),
+        color,
+        ..Default::default()
+    });
+    graphics::draw(ctx, atlas, DrawParams{
+        position: center + (text_pos+Vec2::new(-8.0,20.0)) * scale,
+        scale,
+        clip: get_clip("tetra_text2"),
+        color,
+        ..Default::default()
+    });
+    graphics::draw(ctx, atlas, DrawParams{
+        position: center + (alien_pos+Vec2::new(10.0,30.0)) * scale,
+        scale,
+        clip: get_clip("tetra_alien_eye2"),
+        origin: Vec2::new(0.0,26.0),
+        rotation: animation.get_value("eye"),
+        color,
+    });
+    graphics::draw(ctx, atlas, DrawParams{
+        position: center + gear_pos * scale,
+        scale,
+        clip: get_clip("tetra_gear_wheel"),
+        origin: get_origin("tetra_gear_wheel").unwrap_or_default(),
+        rotation: animation.get_value("gear"),
+        color,
+    });
+    graphics::draw(ctx, atlas, DrawParams{
+        position: center + gear_pos * scale,
+        scale,
+        clip: get_clip("tetra_symbol"),
+        origin: get_origin("tetra_symbol").unwrap_or_default(),
+        color,
+        ..Default::default()
+    });
+}
+
+fn create_animation() -> AnimationClip{
+    let mut alien = Vec::new();
+    let mut eye = Vec::new();
+    for i in 0..=10{
+        let time = i as f64 * 2.0;
+        if i % 2 == 0{
+            alien.push(Keyframe::new(TweenVec2::new(0.0, 0.0), time, EaseInOutQuint));
+            eye.push(Keyframe::new(-0.1, time, EaseInOutQuint));
+        }else{
+            alien.push(Keyframe::new(TweenVec2::new(5.0, 12.5), time, EaseInOutQuint));
+            eye.push(Keyframe::new(0.1, time, EaseInOutQuint));
+        }
+    }
+    AnimationClip::new()
+        .position(alien)
+        .custom("eye", eye)
+        .custom("gear", vec![
+            Keyframe::new(0.0, 0.0, Linear),
+            Keyframe::new(12.566_371, 20.0, Linear)])
+        .mode(PlaybackMode::Loop)
+}
+
 fn get_clip(name: &str) -> Option<Rectangle>{
     match name{
         "tetra_symbol" => Some(Rectangle::new(282.0,0.0,124.0,142.0)),
@@ -0,0 +1,206 @@
+use std::time::Duration;
+use tetra::audio::Sound;
+use tetra::{Context, time};
+use crate::sound::SoundPool;
+use crate::sound::mixer::{Mixer, Bus};
+use crate::sound::spatial::{Emitter, Listener};
+use crate::sound::voices::VoiceLimiter;
+#[cfg(feature = "randomize")]
+use crate::sound::random_index;
+
+/// Several variants of a sound, e.g. footsteps.
+/// With the feature randomize a random variant (never the same twice in a row), volume and pitch is used,
+/// without it the variants are played one after the other with the middle of the ranges.
+/// The cooldown is off by default, with a cooldown update (or tick) has to be called every frame like for the Timer.
+pub struct SoundGroup {
+	variants: Vec<SoundPool>,
+	last: Option<usize>,
+	volume: (f32, f32),
+	pitch: (f32, f32),
+	cooldown: Duration,
+	since_play: Option<Duration>,
+}
+
+#[allow(dead_code)]
+impl SoundGroup {
+	pub fn new(ctx: &Context, sounds: Vec<Sound>, instances_per_variant: usize) -> tetra::Result<SoundGroup>{
+		let mut variants = Vec::with_capacity(sounds.len());
+		for sound in sounds{
+			variants.push(SoundPool::new(ctx, sound, instances_per_variant)?);
+		}
+		Ok(SoundGroup::with_variants(variants))
+	}
+
+	fn with_variants(variants: Vec<SoundPool>) -> SoundGroup{
+		SoundGroup{
+			variants,
+			last: None,
+			volume: (1.0, 1.0),
+			pitch: (1.0, 1.0),
+			cooldown: Duration::from_secs(0),
+			since_play: None,
+		}
+	}
+
+	pub fn volume_range(mut self, min: f32, max: f32) -> Self{
+		self.volume = (min, max.max(min));
+		self
+	}
+
+	/// speed of the playback, e.g. 0.9 - 1.1
+	pub fn pitch_range(mut self, min: f32, max: f32) -> Self{
+		self.pitch = (min, max.max(min));
+		self
+	}
+
+	/// minimum time between two plays of any variant of the group, e.g. 50ms so a sound isn't triggered many times in one frame,
+	/// default is none, it only advances with update or tick
+	pub fn cooldown(mut self, cooldown: Duration) -> Self{
+		self.cooldown = cooldown;
+		self
+	}
+
+	pub fn bus(mut self, mixer: &Mixer, bus: Bus) -> Self{
+		self.variants = self.variants.into_iter().map(|v| v.bus(mixer, bus)).collect();
+		self
+	}
+
+	pub fn priority(mut self, priority: i32) -> Self{
+		self.variants = self.variants.into_iter().map(|v| v.priority(priority)).collect();
+		self
+	}
+
+	pub fn voices(mut self, limiter: &VoiceLimiter) -> Self{
+		self.variants = self.variants.into_iter().map(|v| v.voices(limiter)).collect();
+		self
+	}
+
+	pub fn variant_count(&self) -> usize{
+		self.variants.len()
+	}
+
+	/// index of the variant which was played last
+	pub fn last_variant(&self) -> Option<usize>{
+		self.last
+	}
+
+	pub fn is_cooling_down(&self) -> bool{
+		matches!(self.since_play, Some(since_play) if since_play < self.cooldown)
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	pub fn tick(&mut self, delta: Duration){
+		if let Some(since_play) = self.since_play.as_mut(){
+			*since_play += delta;
+		}
+	}
+
+	/// returns false if nothing was played because of the cooldown or the voice limit,
+	/// with a cooldown update (or tick) has to be called every frame, otherwise it never ends
+	pub fn play(&mut self) -> bool{
+		match self.next_variant(){
+			Some(index) => {
+				let (volume, pitch) = (self.pick(self.volume), self.pick(self.pitch));
+				let played = self.variants[index].play(volume, pitch);
+				if played{
+					self.played(index);
+				}
+				played
+			}
+			None => false,
+		}
+	}
+
+	/// plays at the position of the emitter, the volume of the emitter is multiplied with the volume range
+	pub fn play_at(&mut self, emitter: Emitter, listener: &Listener) -> bool{
+		match self.next_variant(){
+			Some(index) => {
+				let emitter = emitter.volume(emitter.volume * self.pick(self.volume));
+				let pitch = self.pick(self.pitch);
+				let played = self.variants[index].play_at(emitter, listener, pitch).is_some();
+				if played{
+					self.played(index);
+				}
+				played
+			}
+			None => false,
+		}
+	}
+
+	/// call it every frame if sounds were played with play_at
+	pub fn update_listener(&mut self, listener: &Listener){
+		for variant in self.variants.iter_mut(){
+			variant.update_listener(listener);
+		}
+	}
+
+	fn next_variant(&mut self) -> Option<usize>{
+		if self.variants.is_empty() || self.is_cooling_down(){
+			return None;
+		}
+		Some(self.pick_variant())
+	}
+
+	// a play which was refused by the voice limiter does not count for the cooldown and the variant
+	fn played(&mut self, index: usize){
+		self.last = Some(index);
+		self.since_play = Some(Duration::from_secs(0));
+	}
+
+	#[cfg(feature = "randomize")]
+	fn pick_variant(&self) -> usize{
+		random_index(self.variants.len(), self.last)
+	}
+
+	#[cfg(not(feature = "randomize"))]
+	fn pick_variant(&self) -> usize{
+		match self.last{
+			Some(last) => (last + 1) % self.variants.len(),
+			None => 0,
+		}
+	}
+
+	#[cfg(feature = "randomize")]
+	fn pick(&self, (min, max): (f32, f32)) -> f32{
+		use rand::Rng;
+		if max > min{
+			crate::utils::random::with_rng(|rng| rng.gen_range(min..max))
+		}else{
+			min
+		}
+	}
+
+	#[cfg(not(feature = "randomize"))]
+	fn pick(&self, (min, max): (f32, f32)) -> f32{
+		(min + max) / 2.0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn no_cooldown_without_update(){
+		let mut group = SoundGroup::with_variants(Vec::new());
+		group.played(0);
+		assert!(!group.is_cooling_down());
+		group.played(0);
+		assert!(!group.is_cooling_down());
+	}
+
+	#[test]
+	fn cooldown_ends_with_tick(){
+		let mut group = SoundGroup::with_variants(Vec::new()).cooldown(Duration::from_millis(50));
+		assert!(!group.is_cooling_down());
+		group.played(0);
+		assert!(group.is_cooling_down());
+		group.tick(Duration::from_millis(30));
+		assert!(group.is_cooling_down());
+		group.tick(Duration::from_millis(20));
+		assert!(!group.is_cooling_down());
+	}
+}
@@ -0,0 +1,253 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use tetra::audio::SoundInstance;
+use log::debug;
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub enum Bus {
+	Master,
+	Music,
+	Sfx,
+	Voice,
+	Ui,
+}
+
+/// volumes of the buses which can be stored in the config, the master volume is Config::master_volume
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub struct MixerSettings {
+	pub music: f32,
+	pub sfx: f32,
+	pub voice: f32,
+	pub ui: f32,
+	#[cfg_attr(feature = "ron_file", serde(default))]
+	pub muted: Vec<Bus>,
+}
+
+impl Default for MixerSettings {
+	fn default() -> MixerSettings {
+		MixerSettings{
+			music: 1.0,
+			sfx: 1.0,
+			voice: 1.0,
+			ui: 1.0,
+			muted: Vec::new(),
+		}
+	}
+}
+
+struct Route {
+	id: usize,
+	bus: Bus,
+	instance: SoundInstance,
+	// own volume of the channel, gone when the channel is dropped
+	volume: Weak<Cell<f32>>,
+}
+
+struct MixerState {
+	master: f32,
+	settings: MixerSettings,
+	routes: Vec<Route>,
+	next_id: usize,
+}
+
+impl MixerState {
+	fn bus_volume(&self, bus: Bus) -> f32{
+		if self.settings.muted.contains(&bus) || self.settings.muted.contains(&Bus::Master){
+			return 0.0;
+		}
+		let volume = match bus{
+			Bus::Master => 1.0,
+			Bus::Music => self.settings.music,
+			Bus::Sfx => self.settings.sfx,
+			Bus::Voice => self.settings.voice,
+			Bus::Ui => self.settings.ui,
+		};
+		volume * self.master
+	}
+
+	fn apply(&mut self){
+		self.routes.retain(|r| r.volume.strong_count() > 0);
+		for route in self.routes.iter(){
+			self.apply_route(route);
+		}
+	}
+
+	fn apply_route(&self, route: &Route){
+		if let Some(volume) = route.volume.upgrade(){
+			route.instance.set_volume(volume.get() * self.bus_volume(route.bus));
+		}
+	}
+}
+
+/// Shared handle, every clone changes the same buses.
+/// SoundPools and Music which are routed through a bus are updated immediately when a bus changes.
+#[derive(Clone)]
+pub struct Mixer {
+	state: Rc<RefCell<MixerState>>,
+}
+
+#[allow(dead_code)]
+impl Mixer {
+	pub fn new() -> Mixer{
+		Mixer::from_settings(1.0, MixerSettings::default())
+	}
+
+	pub fn from_settings(master_volume: f32, settings: MixerSettings) -> Mixer{
+		Mixer{
+			state: Rc::new(RefCell::new(MixerState{
+				master: master_volume.max(0.0),
+				settings,
+				routes: Vec::new(),
+				next_id: 0,
+			})),
+		}
+	}
+
+	pub fn set_volume(&self, bus: Bus, volume: f32){
+		let volume = volume.max(0.0);
+		let mut state = self.state.borrow_mut();
+		match bus{
+			Bus::Master => state.master = volume,
+			Bus::Music => state.settings.music = volume,
+			Bus::Sfx => state.settings.sfx = volume,
+			Bus::Voice => state.settings.voice = volume,
+			Bus::Ui => state.settings.ui = volume,
+		}
+		debug!("mixer {:?} volume {}", bus, volume);
+		state.apply();
+	}
+
+	/// volume of the bus itself, without master and mute
+	pub fn get_volume(&self, bus: Bus) -> f32{
+		let state = self.state.borrow();
+		match bus{
+			Bus::Master => state.master,
+			Bus::Music => state.settings.music,
+			Bus::Sfx => state.settings.sfx,
+			Bus::Voice => state.settings.voice,
+			Bus::Ui => state.settings.ui,
+		}
+	}
+
+	/// volume which is applied to the sounds of the bus (bus * master, 0 if muted)
+	pub fn get_output_volume(&self, bus: Bus) -> f32{
+		self.state.borrow().bus_volume(bus)
+	}
+
+	pub fn set_muted(&self, bus: Bus, muted: bool){
+		let mut state = self.state.borrow_mut();
+		state.settings.muted.retain(|b| *b != bus);
+		if muted{
+			state.settings.muted.push(bus);
+		}
+		state.apply();
+	}
+
+	pub fn is_muted(&self, bus: Bus) -> bool{
+		self.state.borrow().settings.muted.contains(&bus)
+	}
+
+	pub fn toggle_muted(&self, bus: Bus){
+		self.set_muted(bus, !self.is_muted(bus));
+	}
+
+	/// the current volumes, e.g. to store them in the config
+	pub fn get_settings(&self) -> MixerSettings{
+		self.state.borrow().settings.clone()
+	}
+
+	/// number of sound instances which are routed through the mixer
+	pub fn routed(&self) -> usize{
+		let mut state = self.state.borrow_mut();
+		state.routes.retain(|r| r.volume.strong_count() > 0);
+		state.routes.len()
+	}
+
+	fn add_route(&self, bus: Bus, instance: SoundInstance, volume: &Rc<Cell<f32>>) -> usize{
+		let mut state = self.state.borrow_mut();
+		let id = state.next_id;
+		state.next_id += 1;
+		let route = Route{
+			id,
+			bus,
+			instance,
+			volume: Rc::downgrade(volume),
+		};
+		state.apply_route(&route);
+		state.routes.push(route);
+		id
+	}
+
+	fn update_route(&self, id: usize){
+		let state = self.state.borrow();
+		if let Some(route) = state.routes.iter().find(|r| r.id == id){
+			state.apply_route(route);
+		}
+	}
+}
+
+impl Default for Mixer {
+	fn default() -> Mixer {
+		Mixer::new()
+	}
+}
+
+/// SoundInstance with its own volume, which is multiplied with the volume of a bus if it is routed
+#[derive(Debug, Clone)]
+pub struct Channel {
+	instance: SoundInstance,
+	route: Option<(Mixer, usize)>,
+	volume: Rc<Cell<f32>>,
+}
+
+#[allow(dead_code)]
+impl Channel {
+	pub fn new(instance: SoundInstance) -> Channel{
+		Channel{
+			instance,
+			route: None,
+			volume: Rc::new(Cell::new(1.0)),
+		}
+	}
+
+	pub fn route(&mut self, mixer: &Mixer, bus: Bus){
+		let id = mixer.add_route(bus, self.instance.clone(), &self.volume);
+		self.route = Some((mixer.clone(), id));
+	}
+
+	pub fn set_volume(&mut self, volume: f32){
+		self.volume.set(volume);
+		match self.route.as_ref(){
+			Some((mixer, id)) => mixer.update_route(*id),
+			None => self.instance.set_volume(volume),
+		}
+	}
+
+	/// own volume, without the bus
+	pub fn get_volume(&self) -> f32{
+		self.volume.get()
+	}
+
+	// shared with the mixer and the voice limiter, which only keep a weak reference
+	pub(crate) fn get_shared_volume(&self) -> &Rc<Cell<f32>>{
+		&self.volume
+	}
+
+	pub fn instance(&self) -> &SoundInstance{
+		&self.instance
+	}
+}
+
+impl std::fmt::Debug for Mixer {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		let state = self.state.borrow();
+		f.debug_struct("Mixer")
+			.field("master", &state.master)
+			.field("settings", &state.settings)
+			.finish()
+	}
+}
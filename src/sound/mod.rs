@@ -1,16 +1,29 @@
 // https://gist.github.com/17cupsofcoffee/f5082a13626ddf0030075d542262c728
 
+pub mod group;
+pub mod mixer;
 pub mod music;
+pub mod spatial;
+pub mod voices;
 
-use tetra::audio::{Sound, SoundInstance};
+use tetra::audio::{Sound, SoundState};
 use tetra::Context;
+use crate::sound::mixer::{Channel, Mixer, Bus};
+use crate::sound::spatial::{Emitter, Listener};
+use crate::sound::voices::VoiceLimiter;
+use crate::TetraVec2;
 
 #[derive(Clone)]
 pub struct SoundPool {
 	sound: Sound,
-	instances: Vec<SoundInstance>,
+	instances: Vec<Channel>,
+	// the instances of the right side if the pool is panned, then instances are the left side
+	right: Vec<Channel>,
+	emitters: Vec<Option<Emitter>>,
 	next: usize,
 	single: bool,
+	priority: i32,
+	limiter: Option<VoiceLimiter>,
 }
 
 #[allow(dead_code)]
@@ -19,14 +32,18 @@ impl SoundPool {
 		let mut instances = Vec::with_capacity(instance_count);
 
 		for _ in 0..instance_count {
-			instances.push(sound.spawn(ctx)?);
+			instances.push(Channel::new(sound.spawn(ctx)?));
 		}
 
 		Ok(SoundPool {
 			sound,
+			emitters: vec![None; instance_count],
 			instances,
+			right: Vec::new(),
 			next: 0,
-			single: false
+			single: false,
+			priority: 0,
+			limiter: None,
 		})
 	}
 
@@ -35,32 +52,153 @@ impl SoundPool {
 		let mut instances = Vec::with_capacity(1);
 		let instance = sound.spawn(ctx)?;
 		instance.set_repeating(true);
-		instance.set_volume(0.0);
 		instance.play();
-		instances.push(instance);
+		let mut channel = Channel::new(instance);
+		channel.set_volume(0.0);
+		instances.push(channel);
 
 		Ok(SoundPool {
 			sound,
+			emitters: vec![None],
 			instances,
+			right: Vec::new(),
 			next: 0,
-			single: true
+			single: true,
+			priority: 0,
+			limiter: None,
 		})
 	}
 
-	pub fn play(&mut self, volume: f32, speed: f32) {
-		let instance = &self.instances[self.next];
+	/// Stereo panning for play_at: the left sound is only heard on the left side and the right sound on the right side,
+	/// both are played at the same time with the volumes of the pan, see spatial::split_wav.
+	/// The VoiceLimiter counts a panned sound once, with the volume of its left side.
+	pub fn panned(ctx: &Context, left: Sound, right: Sound, instance_count: usize) -> tetra::Result<SoundPool>{
+		let mut pool = SoundPool::new(ctx, left, instance_count)?;
+		for _ in 0..instance_count {
+			pool.right.push(Channel::new(right.spawn(ctx)?));
+		}
+		Ok(pool)
+	}
+
+	/// the volume of play() is multiplied with the volume of the bus
+	pub fn bus(mut self, mixer: &Mixer, bus: Bus) -> Self{
+		for channel in self.instances.iter_mut().chain(self.right.iter_mut()){
+			channel.route(mixer, bus);
+		}
+		self
+	}
+
+	/// higher priorities can steal the voices of lower priorities, default is 0
+	pub fn priority(mut self, priority: i32) -> Self{
+		self.priority = priority;
+		self
+	}
+
+	/// the number of voices is limited over all pools which use the same limiter
+	pub fn voices(mut self, limiter: &VoiceLimiter) -> Self{
+		self.limiter = Some(limiter.clone());
+		self
+	}
+
+	/// returns false if no voice was free
+	pub fn play(&mut self, volume: f32, speed: f32) -> bool{
+		self.play_next(volume, 0.0, speed, None).is_some()
+	}
+
+	/// plays at the position of the emitter, returns the index of the instance,
+	/// a panned pool plays it on the side of the emitter
+	pub fn play_at(&mut self, emitter: Emitter, listener: &Listener, speed: f32) -> Option<usize>{
+		self.play_next(emitter.get_volume(listener), emitter.get_pan(listener), speed, Some(emitter))
+	}
+
+	/// number of instances which are playing
+	pub fn playing(&self) -> usize{
+		if self.single{
+			return 0;
+		}
+		self.instances.iter().filter(|c| c.instance().state() == SoundState::Playing).count()
+	}
+
+	/// call it every frame when the listener or the emitters have moved
+	pub fn update_listener(&mut self, listener: &Listener){
+		for index in 0..self.instances.len(){
+			if let Some(emitter) = self.emitters[index]{
+				self.set_volume(index, emitter.get_volume(listener), emitter.get_pan(listener));
+			}
+			// the left side was stopped by the voice limiter
+			if let Some(right) = self.right.get(index){
+				if self.instances[index].instance().state() != SoundState::Playing && right.instance().state() == SoundState::Playing{
+					right.instance().stop();
+				}
+			}
+		}
+	}
 
-		instance.set_volume(volume);
-		instance.set_speed(speed);
+	pub fn set_emitter_position(&mut self, index: usize, position: TetraVec2){
+		if let Some(Some(emitter)) = self.emitters.get_mut(index){
+			emitter.position = position;
+		}
+	}
+
+	pub fn get_emitter(&self, index: usize) -> Option<&Emitter>{
+		self.emitters.get(index)?.as_ref()
+	}
 
-		// If we've looped back to an instance before it stops playing,
-		// rewind it and play again.
+	// a stopped instance is preferred, otherwise the oldest is restarted,
+	// both go through the voice limiter
+	fn play_next(&mut self, volume: f32, pan: f32, speed: f32, emitter: Option<Emitter>) -> Option<usize>{
 		if !self.single{
-			instance.stop();
-			instance.play();
+			let count = self.instances.len();
+			if let Some(free) = (0..count).map(|i| (self.next + i) % count).find(|i| self.instances[*i].instance().state() != SoundState::Playing){
+				self.next = free;
+			}
+		}
+		if let Some(limiter) = self.limiter.as_ref(){
+			if !limiter.start(&self.instances[self.next], self.priority){
+				return None;
+			}
+		}
+		let index = self.next;
+		self.emitters[index] = emitter;
+		self.set_volume(index, volume, pan);
+		let instances = std::iter::once(&self.instances[index]).chain(self.right.get(index));
+		for instance in instances.map(|c| c.instance()){
+			instance.set_speed(speed);
+
+			// If we've looped back to an instance before it stops playing,
+			// rewind it and play again.
+			if !self.single{
+				instance.stop();
+				instance.play();
+			}
 		}
 
 		self.next = (self.next + 1) % self.instances.len();
+		Some(index)
+	}
+
+	// a panned pool splits the volume between the sides
+	fn set_volume(&mut self, index: usize, volume: f32, pan: f32){
+		match self.right.get_mut(index){
+			Some(right) => {
+				let (left_gain, right_gain) = spatial::pan_gains(pan);
+				self.instances[index].set_volume(volume * left_gain);
+				right.set_volume(volume * right_gain);
+			}
+			None => self.instances[index].set_volume(volume),
+		}
 	}
 }
 
+/// random index, but not the same as the last one
+#[cfg(feature = "randomize")]
+pub(crate) fn random_index(len: usize, last: Option<usize>) -> usize{
+	use rand::Rng;
+	crate::utils::random::with_rng(|rng| match last{
+		Some(last) if len > 1 => {
+			let index = rng.gen_range(0..len - 1);
+			if index >= last { index + 1 } else { index }
+		}
+		_ => rng.gen_range(0..len),
+	})
+}
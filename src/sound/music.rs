@@ -1,11 +1,18 @@
-use tetra::audio::{SoundInstance, Sound, SoundState};
+use tetra::audio::{Sound, SoundState};
 use crate::utils::timer::Timer;
-use tetra::Context;
+use tetra::{Context, time};
+use std::collections::{HashMap, VecDeque};
+use std::f32::consts::FRAC_PI_2;
+use std::time::Duration;
+use log::{debug, error};
+use crate::sound::mixer::{Channel, Mixer, Bus};
+#[cfg(feature = "randomize")]
+use crate::sound::random_index;
 
 pub struct Music{
 	repeat_interval: Timer,
 	fade_timeframe: Timer,
-	music_instance: SoundInstance,
+	music_instance: Channel,
 	volume: f32,
 	state: MusicState,
 	repeat: bool,
@@ -19,28 +26,45 @@ impl Music {
 		Ok(Music{
 			repeat_interval: Timer::new_sec(repeat_interval_sec),
 			fade_timeframe: Timer::new(1700),
-			music_instance,
+			music_instance: Channel::new(music_instance),
 			volume: 1.0,
 			state: MusicState::Waiting,
 			repeat
 		})
 	}
 
+	/// the volume is multiplied with the volume of the bus
+	pub fn bus(mut self, mixer: &Mixer, bus: Bus) -> Self{
+		self.music_instance.route(mixer, bus);
+		self
+	}
+
 	pub fn volume(&mut self, volume: f32){
 		self.volume = volume;
 	}
 
+	/// duration of fade in and fade out, default is 1700ms
+	pub fn fade_duration(&mut self, duration: Duration){
+		self.fade_timeframe = Timer::from_duration(duration);
+	}
+
 	pub fn repeat_interval(&mut self,repeat_interval: u64){
 		self.repeat_interval = Timer::new_sec(repeat_interval);
 	}
 
 	pub fn start(&mut self){
-		self.music_instance.play();
+		self.music_instance.instance().play();
 		self.fade_timeframe.restart();
 		self.state = MusicState::FadeIn;
 	}
 
-	pub fn update(&mut self){
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	pub fn tick(&mut self, delta: Duration){
+		self.repeat_interval.tick(delta);
+		self.fade_timeframe.tick(delta);
 		match self.state{
 			MusicState::Waiting =>{
 				if self.repeat && self.repeat_interval.finished(){
@@ -54,7 +78,7 @@ impl Music {
 				self.music_instance.set_volume(self.volume * self.fade_timeframe.value());
 			}
 			MusicState::Playing =>{
-				if let SoundState::Stopped = self.music_instance.state(){
+				if let SoundState::Stopped = self.music_instance.instance().state(){
 					self.repeat_interval.restart();
 					self.state = MusicState::Waiting;
 				}
@@ -62,23 +86,22 @@ impl Music {
 			MusicState::FadeOut =>{
 				if self.fade_timeframe.finished(){
 					self.state = MusicState::Waiting;
-					self.music_instance.stop();
+					self.music_instance.instance().stop();
 				}
-				self.music_instance.set_volume(self.volume * 1.0 - self.fade_timeframe.value());
+				self.music_instance.set_volume(self.volume * (1.0 - self.fade_timeframe.value()));
 			}
 		}
 	}
 
 	pub fn stop(&mut self){
 		self.state = MusicState::Waiting;
-		self.music_instance.stop()
+		self.music_instance.instance().stop()
 	}
 
 	pub fn end(&mut self){
 		self.state = MusicState::FadeOut;
 		self.fade_timeframe.restart()
 	}
-
 }
 
 enum MusicState{
@@ -87,3 +110,557 @@ enum MusicState{
 	FadeIn,
 	FadeOut,
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FadeCurve {
+	Linear,
+	/// starts slow
+	EaseIn,
+	/// starts fast
+	EaseOut,
+	/// keeps the loudness constant during a cross-fade
+	EqualPower,
+}
+
+impl FadeCurve {
+	/// volume of a fade in at progress 0.0 - 1.0, a fade out uses 1.0 - progress
+	pub fn gain(self, progress: f32) -> f32{
+		let t = progress.clamp(0.0, 1.0);
+		match self{
+			FadeCurve::Linear => t,
+			FadeCurve::EaseIn => t * t,
+			FadeCurve::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+			FadeCurve::EqualPower => (t * FRAC_PI_2).sin(),
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MusicEvent {
+	TrackStarted(String),
+	TrackFinished(String),
+}
+
+struct Track {
+	name: String,
+	channel: Channel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Fade {
+	None,
+	In(Duration, Duration),
+	Out(Duration, Duration),
+}
+
+struct Deck {
+	track: usize,
+	fade: Fade,
+	curve: FadeCurve,
+}
+
+impl Deck {
+	fn gain(&self) -> f32{
+		match self.fade{
+			Fade::None => 1.0,
+			Fade::In(elapsed, duration) => self.curve.gain(progress(elapsed, duration)),
+			Fade::Out(elapsed, duration) => self.curve.gain(1.0 - progress(elapsed, duration)),
+		}
+	}
+
+	fn tick(&mut self, delta: Duration){
+		self.fade = match self.fade{
+			Fade::In(elapsed, duration) if elapsed + delta >= duration => Fade::None,
+			Fade::In(elapsed, duration) => Fade::In(elapsed + delta, duration),
+			Fade::Out(elapsed, duration) => Fade::Out((elapsed + delta).min(duration), duration),
+			Fade::None => Fade::None,
+		}
+	}
+
+	fn faded_out(&self) -> bool{
+		matches!(self.fade, Fade::Out(elapsed, duration) if elapsed >= duration)
+	}
+}
+
+// the next track of the playlist in order
+fn following_track(last: Option<usize>, len: usize, repeat: bool) -> Option<usize>{
+	match last{
+		_ if len == 0 => None,
+		None => Some(0),
+		Some(index) if index + 1 < len => Some(index + 1),
+		Some(_) if repeat => Some(0),
+		Some(_) => None,
+	}
+}
+
+fn progress(elapsed: Duration, duration: Duration) -> f32{
+	if duration.as_secs_f32() <= 0.0{
+		1.0
+	}else{
+		elapsed.as_secs_f32() / duration.as_secs_f32()
+	}
+}
+
+/// Plays a playlist and cross-fades between the tracks.
+/// play("battle") fades over to another track at any time.
+pub struct MusicPlayer {
+	tracks: Vec<Track>,
+	queue: VecDeque<usize>,
+	current: Option<Deck>,
+	fading_out: Vec<Deck>,
+	fade: Duration,
+	curve: FadeCurve,
+	volume: f32,
+	repeat: bool,
+	loop_track: bool,
+	#[cfg(feature = "randomize")]
+	shuffle: bool,
+	route: Option<(Mixer, Bus)>,
+	events: Vec<MusicEvent>,
+}
+
+#[allow(dead_code)]
+impl MusicPlayer {
+	pub fn new() -> MusicPlayer{
+		MusicPlayer{
+			tracks: Vec::new(),
+			queue: VecDeque::new(),
+			current: None,
+			fading_out: Vec::new(),
+			fade: Duration::from_millis(1700),
+			curve: FadeCurve::EqualPower,
+			volume: 1.0,
+			repeat: true,
+			loop_track: false,
+			#[cfg(feature = "randomize")]
+			shuffle: false,
+			route: None,
+			events: Vec::new(),
+		}
+	}
+
+	/// default cross-fade duration
+	pub fn fade(mut self, duration: Duration) -> Self{
+		self.fade = duration;
+		self
+	}
+
+	/// default fade curve
+	pub fn curve(mut self, curve: FadeCurve) -> Self{
+		self.curve = curve;
+		self
+	}
+
+	/// the tracks are routed through the bus of the mixer
+	pub fn bus(mut self, mixer: &Mixer, bus: Bus) -> Self{
+		for track in self.tracks.iter_mut(){
+			track.channel.route(mixer, bus);
+		}
+		self.route = Some((mixer.clone(), bus));
+		self
+	}
+
+	/// the tracks are played in the order in which they were added
+	pub fn add_track(&mut self, ctx: &Context, name: &str, sound: &Sound) -> tetra::Result{
+		let mut channel = Channel::new(sound.spawn(ctx)?);
+		channel.set_volume(0.0);
+		if let Some((mixer, bus)) = self.route.as_ref(){
+			channel.route(mixer, *bus);
+		}
+		self.tracks.push(Track{
+			name: name.to_string(),
+			channel,
+		});
+		Ok(())
+	}
+
+	/// start again with the first track when the last has finished, default is true
+	pub fn set_repeat(&mut self, repeat: bool){
+		self.repeat = repeat;
+	}
+
+	/// repeat the current track instead of playing the next one
+	pub fn set_loop_track(&mut self, loop_track: bool){
+		self.loop_track = loop_track;
+		for track in self.tracks.iter(){
+			track.channel.instance().set_repeating(loop_track);
+		}
+	}
+
+	/// picks a random track (not the same as before) instead of the next one
+	#[cfg(feature = "randomize")]
+	pub fn set_shuffle(&mut self, shuffle: bool){
+		self.shuffle = shuffle;
+	}
+
+	pub fn set_volume(&mut self, volume: f32){
+		self.volume = volume;
+		self.apply_volumes();
+	}
+
+	pub fn get_volume(&self) -> f32{
+		self.volume
+	}
+
+	pub fn current_track(&self) -> Option<&str>{
+		self.current.as_ref().map(|d| self.tracks[d.track].name.as_str())
+	}
+
+	pub fn is_playing(&self) -> bool{
+		self.current.is_some()
+	}
+
+	pub fn is_fading(&self) -> bool{
+		!self.fading_out.is_empty() || matches!(self.current.as_ref().map(|d| d.fade), Some(Fade::In(..)))
+	}
+
+	/// cross-fades to the track with the default fade
+	pub fn play(&mut self, name: &str){
+		self.play_with(name, self.fade, self.curve);
+	}
+
+	pub fn play_with(&mut self, name: &str, fade: Duration, curve: FadeCurve){
+		match self.find(name){
+			Some(index) => self.switch_to(index, fade, curve),
+			None => error!("music track {} not found!", name),
+		}
+	}
+
+	/// is played after the current track, before the playlist continues
+	pub fn queue(&mut self, name: &str){
+		match self.find(name){
+			Some(index) => self.queue.push_back(index),
+			None => error!("music track {} not found!", name),
+		}
+	}
+
+	pub fn clear_queue(&mut self){
+		self.queue.clear();
+	}
+
+	/// cross-fades to the next track of the queue or playlist
+	pub fn next(&mut self){
+		let last = self.current.as_ref().map(|d| d.track);
+		if let Some(index) = self.next_index(last){
+			self.switch_to(index, self.fade, self.curve);
+		}
+	}
+
+	/// fades out the current track
+	pub fn stop(&mut self){
+		self.stop_with(self.fade);
+	}
+
+	pub fn stop_with(&mut self, fade: Duration){
+		if let Some(mut deck) = self.current.take(){
+			deck.fade = Fade::Out(self.fade_out_start(&deck, fade), fade);
+			self.fading_out.push(deck);
+		}
+		self.queue.clear();
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	pub fn tick(&mut self, delta: Duration){
+		for deck in self.fading_out.iter_mut(){
+			deck.tick(delta);
+		}
+		let tracks = &self.tracks;
+		self.fading_out.retain(|deck| {
+			if deck.faded_out(){
+				tracks[deck.track].channel.instance().stop();
+			}
+			!deck.faded_out()
+		});
+		if let Some(deck) = self.current.as_mut(){
+			deck.tick(delta);
+			if self.tracks[deck.track].channel.instance().state() == SoundState::Stopped{
+				let name = self.tracks[deck.track].name.clone();
+				debug!("music track {} finished", name);
+				self.events.push(MusicEvent::TrackFinished(name));
+				let last = Some(deck.track);
+				self.current = None;
+				if let Some(index) = self.next_index(last){
+					self.switch_to(index, Duration::from_secs(0), self.curve);
+				}
+			}
+		}
+		self.apply_volumes();
+	}
+
+	/// events since the last call
+	pub fn events(&mut self) -> Vec<MusicEvent>{
+		std::mem::take(&mut self.events)
+	}
+
+	fn find(&self, name: &str) -> Option<usize>{
+		self.tracks.iter().position(|t| t.name == name)
+	}
+
+	// last is the track which was played before
+	fn next_index(&mut self, last: Option<usize>) -> Option<usize>{
+		if let Some(index) = self.queue.pop_front(){
+			return Some(index);
+		}
+		if self.tracks.is_empty(){
+			return None;
+		}
+		#[cfg(feature = "randomize")]
+		{
+			if self.shuffle{
+				return Some(random_index(self.tracks.len(), last));
+			}
+		}
+		following_track(last, self.tracks.len(), self.repeat)
+	}
+
+	// the fade out starts at the current volume if the track is still fading in
+	fn fade_out_start(&self, deck: &Deck, fade: Duration) -> Duration{
+		let gain = deck.gain();
+		fade.checked_sub(fade.mul_f32(gain)).unwrap_or_default()
+	}
+
+	fn switch_to(&mut self, index: usize, fade: Duration, curve: FadeCurve){
+		if let Some(mut deck) = self.current.take(){
+			if deck.track == index{
+				self.current = Some(deck);
+				return;
+			}
+			deck.curve = curve;
+			deck.fade = Fade::Out(self.fade_out_start(&deck, fade), fade);
+			self.fading_out.push(deck);
+		}
+		// the track could still be fading out
+		self.fading_out.retain(|d| d.track != index);
+		let instance = self.tracks[index].channel.instance();
+		instance.stop();
+		instance.set_repeating(self.loop_track);
+		instance.play();
+		let name = self.tracks[index].name.clone();
+		debug!("music track {} started", name);
+		self.events.push(MusicEvent::TrackStarted(name));
+		self.current = Some(Deck{
+			track: index,
+			fade: if fade > Duration::from_secs(0) { Fade::In(Duration::from_secs(0), fade) } else { Fade::None },
+			curve,
+		});
+		self.apply_volumes();
+	}
+
+	fn apply_volumes(&mut self){
+		for deck in self.fading_out.iter().chain(self.current.iter()){
+			let volume = self.volume * deck.gain();
+			self.tracks[deck.track].channel.set_volume(volume);
+		}
+	}
+}
+
+impl Default for MusicPlayer {
+	fn default() -> MusicPlayer {
+		MusicPlayer::new()
+	}
+}
+
+/// volume of a stem between 0.0 and 1.0 depending on a parameter, e.g. intensity
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+	parameter: String,
+	from: f32,
+	to: f32,
+}
+
+impl Layer {
+	/// silent at from, full volume at to, to can be smaller than from to fade out
+	pub fn new(parameter: &str, from: f32, to: f32) -> Layer{
+		Layer{
+			parameter: parameter.to_string(),
+			from,
+			to,
+		}
+	}
+
+	pub fn get_volume(&self, value: f32) -> f32{
+		if (self.to - self.from).abs() <= f32::EPSILON{
+			return if value >= self.to { 1.0 } else { 0.0 };
+		}
+		((value - self.from) / (self.to - self.from)).clamp(0.0, 1.0)
+	}
+}
+
+struct Stem {
+	name: String,
+	channel: Channel,
+	layer: Option<Layer>,
+	volume: f32,
+	target: f32,
+}
+
+/// Synchronized stems (e.g. drums, bass, melody) which are started together,
+/// the volume of each stem follows named parameters.
+pub struct LayeredMusic {
+	stems: Vec<Stem>,
+	parameters: HashMap<String, f32>,
+	fade: Duration,
+	volume: f32,
+	playing: bool,
+	route: Option<(Mixer, Bus)>,
+}
+
+#[allow(dead_code)]
+impl LayeredMusic {
+	pub fn new() -> LayeredMusic{
+		LayeredMusic{
+			stems: Vec::new(),
+			parameters: HashMap::new(),
+			fade: Duration::from_millis(1000),
+			volume: 1.0,
+			playing: false,
+			route: None,
+		}
+	}
+
+	/// time for a stem to fade from silent to full volume, default is 1000ms
+	pub fn fade(mut self, fade: Duration) -> Self{
+		self.fade = fade;
+		self
+	}
+
+	pub fn bus(mut self, mixer: &Mixer, bus: Bus) -> Self{
+		for stem in self.stems.iter_mut(){
+			stem.channel.route(mixer, bus);
+		}
+		self.route = Some((mixer.clone(), bus));
+		self
+	}
+
+	/// a stem without layer is always played with full volume
+	pub fn add_stem(&mut self, ctx: &Context, name: &str, sound: &Sound, layer: Option<Layer>) -> tetra::Result{
+		let mut channel = Channel::new(sound.spawn(ctx)?);
+		channel.instance().set_repeating(true);
+		channel.set_volume(0.0);
+		if let Some((mixer, bus)) = self.route.as_ref(){
+			channel.route(mixer, *bus);
+		}
+		let target = self.get_target(layer.as_ref());
+		self.stems.push(Stem{
+			name: name.to_string(),
+			channel,
+			layer,
+			volume: target,
+			target,
+		});
+		Ok(())
+	}
+
+	pub fn set_parameter(&mut self, name: &str, value: f32){
+		self.parameters.insert(name.to_string(), value);
+		for i in 0..self.stems.len(){
+			self.stems[i].target = self.get_target(self.stems[i].layer.as_ref());
+		}
+	}
+
+	pub fn get_parameter(&self, name: &str) -> f32{
+		self.parameters.get(name).copied().unwrap_or(0.0)
+	}
+
+	pub fn set_volume(&mut self, volume: f32){
+		self.volume = volume;
+		self.apply_volumes();
+	}
+
+	/// current volume of the stem without the overall volume
+	pub fn get_stem_volume(&self, name: &str) -> Option<f32>{
+		self.stems.iter().find(|s| s.name == name).map(|s| s.volume)
+	}
+
+	pub fn is_playing(&self) -> bool{
+		self.playing
+	}
+
+	/// starts all stems from the beginning at the same time, with the volumes of the current parameters
+	pub fn play(&mut self){
+		for stem in self.stems.iter_mut(){
+			stem.channel.instance().stop();
+			stem.volume = stem.target;
+		}
+		self.apply_volumes();
+		for stem in self.stems.iter(){
+			stem.channel.instance().play();
+		}
+		self.playing = true;
+	}
+
+	pub fn pause(&mut self){
+		for stem in self.stems.iter(){
+			stem.channel.instance().pause();
+		}
+		self.playing = false;
+	}
+
+	pub fn resume(&mut self){
+		for stem in self.stems.iter(){
+			stem.channel.instance().play();
+		}
+		self.playing = true;
+	}
+
+	pub fn stop(&mut self){
+		for stem in self.stems.iter(){
+			stem.channel.instance().stop();
+		}
+		self.playing = false;
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	/// fades the stems to the volumes of the parameters
+	pub fn tick(&mut self, delta: Duration){
+		let step = if self.fade.as_secs_f32() > 0.0 { delta.as_secs_f32() / self.fade.as_secs_f32() } else { 1.0 };
+		for stem in self.stems.iter_mut(){
+			if stem.volume < stem.target{
+				stem.volume = (stem.volume + step).min(stem.target);
+			}else{
+				stem.volume = (stem.volume - step).max(stem.target);
+			}
+		}
+		self.apply_volumes();
+	}
+
+	fn get_target(&self, layer: Option<&Layer>) -> f32{
+		match layer{
+			Some(layer) => layer.get_volume(self.get_parameter(&layer.parameter)),
+			None => 1.0,
+		}
+	}
+
+	fn apply_volumes(&mut self){
+		for stem in self.stems.iter_mut(){
+			stem.channel.set_volume(self.volume * stem.volume);
+		}
+	}
+}
+
+impl Default for LayeredMusic {
+	fn default() -> LayeredMusic {
+		LayeredMusic::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn following_track_advances_in_order(){
+		assert_eq!(following_track(None, 3, true), Some(0));
+		assert_eq!(following_track(Some(0), 3, true), Some(1));
+		assert_eq!(following_track(Some(1), 3, true), Some(2));
+		assert_eq!(following_track(Some(2), 3, true), Some(0));
+		assert_eq!(following_track(Some(2), 3, false), None);
+		assert_eq!(following_track(None, 0, true), None);
+	}
+}
@@ -0,0 +1,274 @@
+use crate::TetraVec2;
+
+// a smaller min_distance would divide by zero
+const MIN_DISTANCE: f32 = 0.001;
+
+/// how the volume decreases between min_distance and max_distance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Attenuation {
+	/// straight down to 0 at max_distance
+	Linear,
+	/// min_distance / (min_distance + rolloff * (distance - min_distance))
+	Inverse(f32),
+	/// (distance / min_distance) ^ -rolloff
+	Exponential(f32),
+}
+
+impl Attenuation {
+	/// volume factor 0.0 - 1.0 for the distance
+	pub fn gain(self, distance: f32, min_distance: f32, max_distance: f32) -> f32{
+		let min_distance = min_distance.max(MIN_DISTANCE);
+		let max_distance = max_distance.max(min_distance);
+		if distance >= max_distance{
+			return 0.0;
+		}
+		if distance <= min_distance{
+			return 1.0;
+		}
+		let gain = match self{
+			Attenuation::Linear => 1.0 - (distance - min_distance) / (max_distance - min_distance),
+			Attenuation::Inverse(rolloff) => min_distance / (min_distance + rolloff * (distance - min_distance)),
+			Attenuation::Exponential(rolloff) => (distance / min_distance).powf(-rolloff),
+		};
+		gain.clamp(0.0, 1.0)
+	}
+}
+
+/// position of the ears, usually the player or the center of the camera
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+	pub position: TetraVec2,
+	/// horizontal distance at which a sound is only heard on one side
+	pub pan_width: f32,
+}
+
+#[allow(dead_code)]
+impl Listener {
+	pub fn new(position: TetraVec2) -> Listener{
+		Listener{
+			position,
+			pan_width: 320.0,
+		}
+	}
+
+	pub fn pan_width(mut self, pan_width: f32) -> Self{
+		self.pan_width = pan_width;
+		self
+	}
+}
+
+/// position and range of a sound
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Emitter {
+	pub position: TetraVec2,
+	pub volume: f32,
+	pub min_distance: f32,
+	pub max_distance: f32,
+	pub attenuation: Attenuation,
+}
+
+#[allow(dead_code)]
+impl Emitter {
+	pub fn new(position: TetraVec2) -> Emitter{
+		Emitter{
+			position,
+			volume: 1.0,
+			min_distance: 16.0,
+			max_distance: 480.0,
+			attenuation: Attenuation::Linear,
+		}
+	}
+
+	pub fn volume(mut self, volume: f32) -> Self{
+		self.volume = volume;
+		self
+	}
+
+	/// full volume within min_distance, silent from max_distance
+	pub fn distance(mut self, min_distance: f32, max_distance: f32) -> Self{
+		self.min_distance = min_distance.max(MIN_DISTANCE);
+		self.max_distance = max_distance.max(self.min_distance);
+		self
+	}
+
+	pub fn attenuation(mut self, attenuation: Attenuation) -> Self{
+		self.attenuation = attenuation;
+		self
+	}
+
+	/// volume heard by the listener
+	pub fn get_volume(&self, listener: &Listener) -> f32{
+		let distance = self.position.distance(listener.position);
+		self.volume * self.attenuation.gain(distance, self.min_distance, self.max_distance)
+	}
+
+	/// -1.0 (left) to 1.0 (right) from the horizontal offset to the listener
+	pub fn get_pan(&self, listener: &Listener) -> f32{
+		if listener.pan_width <= 0.0{
+			return 0.0;
+		}
+		((self.position.x - listener.position.x) / listener.pan_width).clamp(-1.0, 1.0)
+	}
+}
+
+/// volumes of the left and the right side for the pan, the loudness stays the same (equal power)
+pub fn pan_gains(pan: f32) -> (f32, f32){
+	let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+	(angle.cos(), angle.sin())
+}
+
+/// Splits a PCM wav file (8 or 16 bit, mono or stereo) into two stereo files,
+/// one with only the left side and one with only the right side, e.g. for SoundPool::panned.
+/// A mono file is copied to the side.
+pub fn split_wav(data: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String>{
+	let u16_at = |i: usize| data.get(i..i + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+	let u32_at = |i: usize| data.get(i..i + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+	if data.get(0..4) != Some(b"RIFF") || data.get(8..12) != Some(b"WAVE"){
+		return Err("not a wav file".to_string());
+	}
+	let mut format = None;
+	let mut samples = None;
+	let mut offset = 12;
+	while let (Some(id), Some(size)) = (data.get(offset..offset + 4), u32_at(offset + 4)){
+		let start = offset + 8;
+		let end = start.checked_add(size as usize).filter(|end| *end <= data.len()).ok_or("the wav file is cut off")?;
+		match id{
+			b"fmt " => format = Some((u16_at(start), u16_at(start + 2), u32_at(start + 4), u16_at(start + 14))),
+			b"data" => samples = Some(&data[start..end]),
+			_ => {}
+		}
+		// chunks are padded to an even size
+		offset = end + (size as usize & 1);
+	}
+	let (channels, sample_rate, bits) = match format.ok_or("the wav file has no format")?{
+		(Some(1), Some(channels @ 1..=2), Some(sample_rate), Some(bits @ (8 | 16))) => (channels as usize, sample_rate, bits),
+		_ => return Err("only 8 or 16 bit PCM wav files with one or two channels can be split".to_string()),
+	};
+	let samples = samples.ok_or("the wav file has no data")?;
+	let width = bits as usize / 8;
+	let silence: &[u8] = if bits == 8 { &[128] } else { &[0, 0] };
+	let mut left = Vec::with_capacity(samples.len() / channels * 2);
+	let mut right = Vec::with_capacity(samples.len() / channels * 2);
+	for frame in samples.chunks_exact(width * channels){
+		let (l, r) = frame.split_at(width * (channels - 1));
+		let l = if channels == 1 { r } else { l };
+		left.extend_from_slice(l);
+		left.extend_from_slice(silence);
+		right.extend_from_slice(silence);
+		right.extend_from_slice(r);
+	}
+	Ok((wav(sample_rate, bits, &left), wav(sample_rate, bits, &right)))
+}
+
+// stereo PCM wav file
+fn wav(sample_rate: u32, bits: u16, samples: &[u8]) -> Vec<u8>{
+	let block_align = 2 * bits / 8;
+	let mut data = Vec::with_capacity(44 + samples.len());
+	data.extend_from_slice(b"RIFF");
+	data.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+	data.extend_from_slice(b"WAVEfmt ");
+	data.extend_from_slice(&16u32.to_le_bytes());
+	data.extend_from_slice(&1u16.to_le_bytes());
+	data.extend_from_slice(&2u16.to_le_bytes());
+	data.extend_from_slice(&sample_rate.to_le_bytes());
+	data.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+	data.extend_from_slice(&block_align.to_le_bytes());
+	data.extend_from_slice(&bits.to_le_bytes());
+	data.extend_from_slice(b"data");
+	data.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+	data.extend_from_slice(samples);
+	data
+}
+
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn near(a: f32, b: f32) -> bool{
+		(a - b).abs() < 1e-5
+	}
+
+	#[test]
+	fn linear(){
+		let linear = Attenuation::Linear;
+		assert!(near(linear.gain(5.0, 10.0, 110.0), 1.0));
+		assert!(near(linear.gain(60.0, 10.0, 110.0), 0.5));
+		assert!(near(linear.gain(110.0, 10.0, 110.0), 0.0));
+		assert!(near(linear.gain(500.0, 10.0, 110.0), 0.0));
+	}
+
+	#[test]
+	fn inverse_and_exponential(){
+		assert!(near(Attenuation::Inverse(1.0).gain(20.0, 10.0, 1000.0), 0.5));
+		assert!(near(Attenuation::Inverse(2.0).gain(20.0, 10.0, 1000.0), 1.0 / 3.0));
+		assert!(near(Attenuation::Exponential(1.0).gain(20.0, 10.0, 1000.0), 0.5));
+		assert!(near(Attenuation::Exponential(2.0).gain(40.0, 10.0, 1000.0), 1.0 / 16.0));
+	}
+
+	#[test]
+	fn zero_min_distance(){
+		for attenuation in [Attenuation::Linear, Attenuation::Inverse(1.0), Attenuation::Exponential(1.0)].iter(){
+			let gain = attenuation.gain(50.0, 0.0, 100.0);
+			assert!(gain.is_finite() && gain > 0.0 && gain < 1.0, "{:?} {}", attenuation, gain);
+			assert!(attenuation.gain(10.0, 0.0, 0.0).is_finite());
+		}
+	}
+
+	#[test]
+	fn emitter_volume(){
+		let listener = Listener::new(TetraVec2::new(0.0, 0.0));
+		let emitter = Emitter::new(TetraVec2::new(30.0, 40.0)).volume(0.5).distance(0.0, 100.0);
+		assert!(near(emitter.get_volume(&listener), 0.25));
+	}
+
+	#[test]
+	fn pan(){
+		let listener = Listener::new(TetraVec2::new(100.0, 0.0)).pan_width(200.0);
+		assert!(near(Emitter::new(TetraVec2::new(100.0, 50.0)).get_pan(&listener), 0.0));
+		assert!(near(Emitter::new(TetraVec2::new(200.0, 0.0)).get_pan(&listener), 0.5));
+		assert!(near(Emitter::new(TetraVec2::new(-500.0, 0.0)).get_pan(&listener), -1.0));
+		assert!(near(Emitter::new(TetraVec2::new(0.0, 0.0)).get_pan(&listener.pan_width(0.0)), 0.0));
+	}
+
+	#[test]
+	fn equal_power(){
+		let (left, right) = pan_gains(0.0);
+		assert!(near(left, right) && near(left * left + right * right, 1.0));
+		let (left, right) = pan_gains(-1.0);
+		assert!(near(left, 1.0) && near(right, 0.0));
+		let (left, right) = pan_gains(1.0);
+		assert!(near(left, 0.0) && near(right, 1.0));
+	}
+
+	fn test_wav(channels: u16, bits: u16, samples: &[u8]) -> Vec<u8>{
+		let mut data = wav(8000, bits, samples);
+		data[22..24].copy_from_slice(&channels.to_le_bytes());
+		data
+	}
+
+	#[test]
+	fn split_stereo(){
+		// two frames of 16 bit stereo
+		let (left, right) = split_wav(&test_wav(2, 16, &[1, 2, 3, 4, 5, 6, 7, 8])).unwrap();
+		assert_eq!(&left[44..], &[1, 2, 0, 0, 5, 6, 0, 0]);
+		assert_eq!(&right[44..], &[0, 0, 3, 4, 0, 0, 7, 8]);
+		assert_eq!(&left[..44], &wav(8000, 16, &[0; 8])[..44]);
+	}
+
+	#[test]
+	fn split_mono(){
+		let (left, right) = split_wav(&test_wav(1, 8, &[10, 20])).unwrap();
+		assert_eq!(&left[44..], &[10, 128, 20, 128]);
+		assert_eq!(&right[44..], &[128, 10, 128, 20]);
+	}
+
+	#[test]
+	fn split_errors(){
+		assert!(split_wav(b"not a wav").is_err());
+		assert!(split_wav(&test_wav(2, 24, &[0; 6])).is_err());
+		let mut cut = test_wav(2, 16, &[0; 8]);
+		cut.truncate(48);
+		assert!(split_wav(&cut).is_err());
+	}
+}
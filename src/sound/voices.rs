@@ -0,0 +1,142 @@
+use std::cell::{Cell, RefCell};
+use std::rc::{Rc, Weak};
+use tetra::audio::{SoundInstance, SoundState};
+use log::debug;
+use crate::sound::mixer::Channel;
+
+/// which voice is stopped when the limit is reached, only voices with the same or a lower priority are stolen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StealMode {
+	Quietest,
+	Oldest,
+}
+
+struct Voice {
+	instance: SoundInstance,
+	priority: i32,
+	volume: Weak<Cell<f32>>,
+	started: u64,
+}
+
+impl Voice {
+	fn is_playing(&self) -> bool{
+		self.volume.strong_count() > 0 && self.instance.state() == SoundState::Playing
+	}
+
+	fn volume(&self) -> f32{
+		self.volume.upgrade().map(|v| v.get()).unwrap_or(0.0)
+	}
+}
+
+struct LimiterState {
+	limit: usize,
+	mode: StealMode,
+	voices: Vec<Voice>,
+	started: u64,
+}
+
+impl LimiterState {
+	fn victim(&self, priority: i32) -> Option<usize>{
+		let candidates = self.voices.iter().enumerate().filter(|(_, v)| v.priority <= priority);
+		match self.mode{
+			StealMode::Quietest => candidates
+				.min_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then(a.volume().partial_cmp(&b.volume()).unwrap_or(std::cmp::Ordering::Equal)))
+				.map(|(i, _)| i),
+			StealMode::Oldest => candidates
+				.min_by(|(_, a), (_, b)| a.priority.cmp(&b.priority).then(a.started.cmp(&b.started)))
+				.map(|(i, _)| i),
+		}
+	}
+}
+
+/// Limits the number of sounds which are played at the same time over all SoundPools which use it.
+/// Shared handle like the Mixer, every clone uses the same voices.
+#[derive(Clone)]
+pub struct VoiceLimiter {
+	state: Rc<RefCell<LimiterState>>,
+}
+
+#[allow(dead_code)]
+impl VoiceLimiter {
+	pub fn new(limit: usize) -> VoiceLimiter{
+		VoiceLimiter{
+			state: Rc::new(RefCell::new(LimiterState{
+				limit: limit.max(1),
+				mode: StealMode::Quietest,
+				voices: Vec::new(),
+				started: 0,
+			})),
+		}
+	}
+
+	pub fn steal(self, mode: StealMode) -> Self{
+		self.state.borrow_mut().mode = mode;
+		self
+	}
+
+	pub fn set_limit(&self, limit: usize){
+		self.state.borrow_mut().limit = limit.max(1);
+	}
+
+	pub fn get_limit(&self) -> usize{
+		self.state.borrow().limit
+	}
+
+	/// number of voices which are playing
+	pub fn playing(&self) -> usize{
+		let mut state = self.state.borrow_mut();
+		state.voices.retain(|v| v.is_playing());
+		state.voices.len()
+	}
+
+	/// playing voices per priority
+	pub fn playing_with_priority(&self, priority: i32) -> usize{
+		let mut state = self.state.borrow_mut();
+		state.voices.retain(|v| v.is_playing());
+		state.voices.iter().filter(|v| v.priority == priority).count()
+	}
+
+	/// frees a voice if the limit is reached, returns false if no voice could be stolen,
+	/// a channel which is still playing is restarted like a stolen voice
+	pub(crate) fn start(&self, channel: &Channel, priority: i32) -> bool{
+		let mut state = self.state.borrow_mut();
+		let volume = Rc::downgrade(channel.get_shared_volume());
+		state.voices.retain(|v| v.is_playing());
+		if let Some(index) = state.voices.iter().position(|v| v.volume.ptr_eq(&volume)){
+			if state.voices[index].priority > priority{
+				return false;
+			}
+			state.voices.remove(index);
+		}
+		if state.voices.len() >= state.limit{
+			match state.victim(priority){
+				Some(index) => {
+					let voice = state.voices.remove(index);
+					debug!("steal voice with priority {}", voice.priority);
+					voice.instance.stop();
+				}
+				None => return false,
+			}
+		}
+		state.started += 1;
+		let started = state.started;
+		state.voices.push(Voice{
+			instance: channel.instance().clone(),
+			priority,
+			volume,
+			started,
+		});
+		true
+	}
+}
+
+impl std::fmt::Debug for VoiceLimiter {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		let state = self.state.borrow();
+		f.debug_struct("VoiceLimiter")
+			.field("limit", &state.limit)
+			.field("mode", &state.mode)
+			.field("voices", &state.voices.len())
+			.finish()
+	}
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+use log::info;
+use serde::Deserialize;
+use serde::de::{Deserializer, MapAccess, SeqAccess, Visitor};
+
+impl AsepriteFile {
+	pub fn new(data: &str) -> Result<AsepriteFile, serde_json::Error>{
+		info!("create SpriteSheet from Aseprite (json).");
+		serde_json::from_str(data)
+	}
+}
+
+/// Aseprite json export, frames can be exported as hash or as array
+#[derive(Debug, Deserialize)]
+pub struct AsepriteFile {
+	#[serde(deserialize_with = "ordered_frames")]
+	pub frames: Vec<Frame>,
+	pub meta: Meta,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub struct Frame {
+	#[serde(default)]
+	pub filename: String,
+	pub frame: Rect,
+	#[serde(default)]
+	pub rotated: bool,
+	#[serde(default)]
+	pub trimmed: bool,
+	#[serde(rename = "spriteSourceSize")]
+	pub sprite_source_size: Rect,
+	#[serde(rename = "sourceSize")]
+	pub source_size: Size,
+	pub duration: u64,
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Deserialize)]
+pub struct Meta {
+	#[serde(default)]
+	pub image: String,
+	#[serde(rename = "frameTags", default)]
+	pub frame_tags: Vec<FrameTag>,
+	#[serde(default)]
+	pub slices: Vec<Slice>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FrameTag {
+	pub name: String,
+	pub from: usize,
+	pub to: usize,
+	#[serde(default)]
+	pub direction: Direction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+	#[default]
+	Forward,
+	Reverse,
+	Pingpong,
+	PingpongReverse,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Slice {
+	pub name: String,
+	pub keys: Vec<SliceKey>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SliceKey {
+	pub frame: usize,
+	pub bounds: Rect,
+	pub pivot: Option<Point>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Rect {
+	pub x: i32,
+	pub y: i32,
+	pub w: i32,
+	pub h: i32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Size {
+	pub w: i32,
+	pub h: i32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct Point {
+	pub x: i32,
+	pub y: i32,
+}
+
+// a hash export must keep the order of the frames, a HashMap would lose it
+fn ordered_frames<'de, D>(deserializer: D) -> Result<Vec<Frame>, D::Error>
+	where
+		D: Deserializer<'de>,
+{
+	struct FramesVisitor;
+
+	impl<'de> Visitor<'de> for FramesVisitor {
+		type Value = Vec<Frame>;
+
+		fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+			formatter.write_str("a list or a map of frames")
+		}
+
+		fn visit_seq<A>(self, mut seq: A) -> Result<Vec<Frame>, A::Error>
+			where
+				A: SeqAccess<'de>,
+		{
+			let mut frames = Vec::new();
+			while let Some(frame) = seq.next_element()? {
+				frames.push(frame);
+			}
+			Ok(frames)
+		}
+
+		fn visit_map<A>(self, mut map: A) -> Result<Vec<Frame>, A::Error>
+			where
+				A: MapAccess<'de>,
+		{
+			let mut frames = Vec::new();
+			while let Some((filename, mut frame)) = map.next_entry::<String, Frame>()? {
+				frame.filename = filename;
+				frames.push(frame);
+			}
+			Ok(frames)
+		}
+	}
+
+	deserializer.deserialize_any(FramesVisitor)
+}
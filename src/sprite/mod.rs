@@ -0,0 +1,499 @@
+#[cfg(feature = "aseprite")]
+pub mod aseprite;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+use log::{debug, error};
+use tetra::graphics::{self, DrawParams, Rectangle, Texture};
+use tetra::math::Vec2;
+use tetra::{Context, time};
+#[cfg(feature = "aseprite")]
+use crate::sprite::aseprite::AsepriteFile;
+use crate::TetraVec2;
+#[cfg(feature = "ron_file")]
+use std::path::Path;
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize};
+#[cfg(feature = "ron_file")]
+use crate::utils::ron::{self, RonFileError};
+
+const DEFAULT_FRAME_DURATION: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteFrame {
+	/// area of the frame on the texture
+	pub clip: Rectangle,
+	/// position of the (trimmed) clip inside the original frame
+	pub offset: TetraVec2,
+	pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "ron_file", derive(Serialize, Deserialize))]
+pub enum Direction {
+	#[default]
+	Forward,
+	Reverse,
+	PingPong,
+	/// starts at the last frame
+	PingPongReverse,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameTag {
+	pub from: usize,
+	pub to: usize,
+	pub direction: Direction,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceKey {
+	/// the key is valid from this frame until the next key
+	pub frame: usize,
+	pub bounds: Rectangle,
+	/// pivot relative to the frame
+	pub pivot: Option<TetraVec2>,
+}
+
+/// frame of a SpriteSheetData
+#[cfg(feature = "ron_file")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameData {
+	pub clip: Rectangle,
+	pub duration_ms: u64,
+}
+
+/// tag of a SpriteSheetData
+#[cfg(feature = "ron_file")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagData {
+	pub from: usize,
+	pub to: usize,
+	#[serde(default)]
+	pub direction: Direction,
+}
+
+/// frame sequences of a sprite sheet in a ron file
+#[cfg(feature = "ron_file")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteSheetData {
+	pub frames: Vec<FrameData>,
+	#[serde(default)]
+	pub tags: HashMap<String, TagData>,
+}
+
+/// frames, tags and slices of a sprite sheet
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+	frames: Vec<SpriteFrame>,
+	tags: HashMap<String, FrameTag>,
+	slices: HashMap<String, Vec<SliceKey>>,
+}
+
+#[allow(dead_code)]
+impl SpriteSheet {
+	/// sprite sheet without tags, the whole sheet is played when no tag is set,
+	/// frames without a duration get the first duration (or 100ms if there is none)
+	pub fn new(clips: &[Rectangle], mut durations: Vec<Duration>) -> SpriteSheet{
+		if clips.len() != durations.len(){
+			let duration = durations.first().copied().unwrap_or(DEFAULT_FRAME_DURATION);
+			for _i in durations.len()..clips.len(){
+				durations.push(duration);
+			}
+		}
+		let frames = clips.iter().zip(durations).map(|(clip, duration)| SpriteFrame{
+			clip: *clip,
+			offset: Vec2::zero(),
+			duration,
+		}).collect();
+		SpriteSheet{
+			frames,
+			tags: HashMap::new(),
+			slices: HashMap::new(),
+		}
+	}
+
+	/// panics if the data is not an Aseprite json export, see try_from_aseprite
+	#[cfg(feature = "aseprite")]
+	pub fn from_aseprite(data: &str) -> SpriteSheet{
+		SpriteSheet::try_from_aseprite(data).expect("invalid Aseprite json")
+	}
+
+	/// e.g. for hot reloading, a half saved file keeps the old sheet
+	#[cfg(feature = "aseprite")]
+	pub fn try_from_aseprite(data: &str) -> Result<SpriteSheet, serde_json::Error>{
+		AsepriteFile::new(data).map(transform_aseprite)
+	}
+
+	#[cfg(feature = "ron_file")]
+	pub fn from_data(data: &SpriteSheetData) -> SpriteSheet{
+		let frames = data.frames.iter().map(|f| SpriteFrame{
+			clip: f.clip,
+			offset: Vec2::zero(),
+			duration: Duration::from_millis(f.duration_ms),
+		}).collect();
+		let mut sheet = SpriteSheet{
+			frames,
+			tags: HashMap::new(),
+			slices: HashMap::new(),
+		};
+		for (name, tag) in data.tags.iter(){
+			sheet.add_tag(name, tag.from, tag.to, tag.direction);
+		}
+		sheet
+	}
+
+	#[cfg(feature = "ron_file")]
+	pub fn from_ron(data: &str) -> Result<SpriteSheet, ron::error::SpannedError>{
+		let data: SpriteSheetData = ron::from_str(data)?;
+		Ok(SpriteSheet::from_data(&data))
+	}
+
+	/// load at runtime, call it again to reload the file
+	#[cfg(feature = "ron_file")]
+	pub fn load<P: AsRef<Path>>(path: P) -> Result<SpriteSheet, RonFileError>{
+		let data: SpriteSheetData = ron::load_file(path)?;
+		Ok(SpriteSheet::from_data(&data))
+	}
+
+	pub fn add_tag(&mut self, name: &str, from: usize, to: usize, direction: Direction){
+		if from > to || to >= self.frames.len(){
+			error!("tag {} ({}-{}) is out of range!", name, from, to);
+			return;
+		}
+		self.tags.insert(name.to_string(), FrameTag{ from, to, direction });
+	}
+
+	pub fn get_frame(&self, frame: usize) -> Option<&SpriteFrame>{
+		self.frames.get(frame)
+	}
+
+	pub fn frame_count(&self) -> usize{
+		self.frames.len()
+	}
+
+	pub fn get_tag(&self, name: &str) -> Option<&FrameTag>{
+		self.tags.get(name)
+	}
+
+	pub fn has_tag(&self, name: &str) -> bool{
+		self.tags.contains_key(name)
+	}
+
+	/// slice key which is valid for the frame
+	pub fn get_slice(&self, name: &str, frame: usize) -> Option<&SliceKey>{
+		self.slices.get(name)?.iter().rev().find(|k| k.frame <= frame)
+	}
+
+	pub fn get_pivot(&self, name: &str, frame: usize) -> Option<TetraVec2>{
+		self.get_slice(name, frame)?.pivot
+	}
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpriteEvent {
+	/// the tag has been played to the end (on every loop for repeating animations)
+	TagFinished(String),
+}
+
+/// plays tags of a SpriteSheet with the duration of each frame
+pub struct SpriteAnimation {
+	sheet: Rc<SpriteSheet>,
+	tag: Option<String>,
+	from: usize,
+	to: usize,
+	direction: Direction,
+	current_frame: usize,
+	forward: bool,
+	timer: Duration,
+	repeating: bool,
+	finished: bool,
+	pivot_slice: Option<String>,
+	events: Vec<SpriteEvent>,
+}
+
+#[allow(dead_code)]
+impl SpriteAnimation {
+	pub fn new(sheet: Rc<SpriteSheet>) -> SpriteAnimation{
+		let to = sheet.frame_count().saturating_sub(1);
+		SpriteAnimation{
+			sheet,
+			tag: None,
+			from: 0,
+			to,
+			direction: Direction::Forward,
+			current_frame: 0,
+			forward: true,
+			timer: Duration::from_secs(0),
+			repeating: true,
+			finished: false,
+			pivot_slice: None,
+			events: Vec::new(),
+		}
+	}
+
+	/// the pivot of this slice is used as origin when drawing
+	pub fn pivot_slice(mut self, name: &str) -> Self{
+		self.pivot_slice = Some(name.to_string());
+		self
+	}
+
+	/// play the tag in a loop, nothing changes if the tag is already playing
+	pub fn play(&mut self, tag: &str){
+		self.set_tag(tag, true);
+	}
+
+	/// play the tag once and stop at the last frame
+	pub fn play_once(&mut self, tag: &str){
+		self.set_tag(tag, false);
+	}
+
+	fn set_tag(&mut self, tag: &str, repeating: bool){
+		if self.tag.as_deref() == Some(tag) && self.repeating == repeating && !self.finished{
+			return;
+		}
+		match self.sheet.get_tag(tag){
+			Some(frame_tag) => {
+				self.from = frame_tag.from;
+				self.to = frame_tag.to;
+				self.direction = frame_tag.direction;
+				self.tag = Some(tag.to_string());
+				self.repeating = repeating;
+				self.restart();
+			}
+			None => debug!("tag {} not found!", tag),
+		}
+	}
+
+	pub fn get_tag(&self) -> Option<&str>{
+		self.tag.as_deref()
+	}
+
+	pub fn restart(&mut self){
+		self.current_frame = match self.direction{
+			Direction::Reverse | Direction::PingPongReverse => self.to,
+			_ => self.from,
+		};
+		self.forward = true;
+		self.timer = Duration::from_secs(0);
+		self.finished = false;
+	}
+
+	pub fn finished(&self) -> bool{
+		self.finished
+	}
+
+	pub fn current_frame(&self) -> usize{
+		self.current_frame
+	}
+
+	/// events since the last call
+	pub fn events(&mut self) -> Vec<SpriteEvent>{
+		std::mem::take(&mut self.events)
+	}
+
+	pub fn advance(&mut self, ctx: &Context){
+		self.advance_by(time::get_delta_time(ctx));
+	}
+
+	pub fn advance_by(&mut self, duration: Duration){
+		if self.finished || self.sheet.frame_count() == 0{
+			return;
+		}
+		self.timer += duration;
+		loop {
+			let frame_length = self.frame_length();
+			if self.timer < frame_length{
+				break;
+			}
+			self.timer -= frame_length;
+			if self.is_last_frame(){
+				self.events.push(SpriteEvent::TagFinished(self.tag.clone().unwrap_or_default()));
+				if !self.repeating{
+					self.finished = true;
+					self.timer = Duration::from_secs(0);
+					break;
+				}
+				self.next_cycle();
+			}else{
+				self.next_frame();
+			}
+		}
+	}
+
+	fn frame_length(&self) -> Duration{
+		match self.sheet.get_frame(self.current_frame){
+			Some(frame) => frame.duration.max(Duration::from_millis(1)),
+			None => Duration::from_millis(1),
+		}
+	}
+
+	fn is_last_frame(&self) -> bool{
+		match self.direction{
+			Direction::Forward => self.current_frame == self.to,
+			Direction::Reverse => self.current_frame == self.from,
+			Direction::PingPong => !self.forward && self.current_frame == self.from,
+			Direction::PingPongReverse => !self.forward && self.current_frame == self.to,
+		}
+	}
+
+	fn next_frame(&mut self){
+		match self.direction{
+			Direction::Forward => self.current_frame += 1,
+			Direction::Reverse => self.current_frame -= 1,
+			Direction::PingPong => {
+				if self.forward && self.current_frame == self.to{
+					self.forward = false;
+					self.current_frame = self.current_frame.saturating_sub(1).max(self.from);
+				}else if self.forward{
+					self.current_frame += 1;
+				}else{
+					self.current_frame -= 1;
+				}
+			}
+			// forward is the first half, from the last frame down to the first
+			Direction::PingPongReverse => {
+				if self.forward && self.current_frame == self.from{
+					self.forward = false;
+					self.current_frame = (self.current_frame + 1).min(self.to);
+				}else if self.forward{
+					self.current_frame -= 1;
+				}else{
+					self.current_frame += 1;
+				}
+			}
+		}
+	}
+
+	fn next_cycle(&mut self){
+		match self.direction{
+			Direction::Forward => self.current_frame = self.from,
+			Direction::Reverse => self.current_frame = self.to,
+			Direction::PingPong => {
+				// the first frame has just been shown
+				self.forward = true;
+				self.current_frame = (self.from + 1).min(self.to);
+			}
+			Direction::PingPongReverse => {
+				// the last frame has just been shown
+				self.forward = true;
+				self.current_frame = self.to.saturating_sub(1).max(self.from);
+			}
+		}
+	}
+
+	/// origin of the current frame, the pivot if a pivot slice is set
+	pub fn get_origin(&self) -> TetraVec2{
+		let mut origin = Vec2::zero();
+		if let Some(name) = self.pivot_slice.as_ref(){
+			if let Some(pivot) = self.sheet.get_pivot(name, self.current_frame){
+				origin = pivot;
+			}
+		}
+		if let Some(frame) = self.sheet.get_frame(self.current_frame){
+			origin -= frame.offset;
+		}
+		origin
+	}
+
+	pub fn draw<P>(&self, ctx: &mut Context, texture: &Texture, params: P)
+		where
+			P: Into<DrawParams>,
+	{
+		if let Some(frame) = self.sheet.get_frame(self.current_frame){
+			let mut params = params.into();
+			if self.pivot_slice.is_some(){
+				params.origin = self.get_origin();
+			}else{
+				params.origin -= frame.offset;
+			}
+			params.clip = Some(frame.clip);
+			graphics::draw(ctx, texture, params)
+		}
+	}
+}
+
+#[cfg(feature = "aseprite")]
+fn transform_aseprite(aseprite_file: AsepriteFile) -> SpriteSheet{
+	let frames = aseprite_file.frames.iter().map(|f| {
+		if f.rotated{
+			error!("frame {} is rotated, rotated frames are not supported!", f.filename);
+		}
+		SpriteFrame{
+			clip: Rectangle::new(f.frame.x as f32, f.frame.y as f32, f.frame.w as f32, f.frame.h as f32),
+			offset: Vec2::new(f.sprite_source_size.x as f32, f.sprite_source_size.y as f32),
+			duration: Duration::from_millis(f.duration),
+		}
+	}).collect();
+	let mut sheet = SpriteSheet{
+		frames,
+		tags: HashMap::new(),
+		slices: HashMap::new(),
+	};
+	for tag in aseprite_file.meta.frame_tags.iter(){
+		let direction = match tag.direction{
+			aseprite::Direction::Forward => Direction::Forward,
+			aseprite::Direction::Reverse => Direction::Reverse,
+			aseprite::Direction::Pingpong => Direction::PingPong,
+			aseprite::Direction::PingpongReverse => Direction::PingPongReverse,
+		};
+		sheet.add_tag(&tag.name, tag.from, tag.to, direction);
+	}
+	for slice in aseprite_file.meta.slices.iter(){
+		let keys = slice.keys.iter().map(|k| SliceKey{
+			frame: k.frame,
+			bounds: Rectangle::new(k.bounds.x as f32, k.bounds.y as f32, k.bounds.w as f32, k.bounds.h as f32),
+			// aseprite stores the pivot relative to the slice
+			pivot: k.pivot.map(|p| Vec2::new((k.bounds.x + p.x) as f32, (k.bounds.y + p.y) as f32)),
+		}).collect();
+		sheet.slices.insert(slice.name.clone(), keys);
+	}
+	debug!("sprite sheet has {} frames and {} tags", sheet.frames.len(), sheet.tags.len());
+	sheet
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn sheet(durations: Vec<Duration>) -> SpriteSheet{
+		let clips: Vec<Rectangle> = (0..4).map(|i| Rectangle::new(i as f32 * 8.0, 0.0, 8.0, 8.0)).collect();
+		SpriteSheet::new(&clips, durations)
+	}
+
+	#[test]
+	fn frames_without_durations(){
+		let sheet = sheet(Vec::new());
+		assert_eq!(sheet.frame_count(), 4);
+		assert_eq!(sheet.get_frame(3).map(|f| f.duration), Some(DEFAULT_FRAME_DURATION));
+	}
+
+	#[test]
+	fn ping_pong_reverse_starts_at_the_last_frame(){
+		let mut sheet = sheet(vec![Duration::from_millis(10)]);
+		sheet.add_tag("idle", 0, 3, Direction::PingPongReverse);
+		let mut animation = SpriteAnimation::new(Rc::new(sheet));
+		animation.play("idle");
+		let mut frames = vec![animation.current_frame()];
+		for _ in 0..8{
+			animation.advance_by(Duration::from_millis(10));
+			frames.push(animation.current_frame());
+		}
+		assert_eq!(frames, vec![3, 2, 1, 0, 1, 2, 3, 2, 1]);
+		assert_eq!(animation.events(), vec![SpriteEvent::TagFinished("idle".to_string())]);
+	}
+
+	#[cfg(feature = "aseprite")]
+	#[test]
+	fn aseprite_errors_are_returned(){
+		let json = r#"{"frames": [{"frame": {"x": 0, "y": 0, "w": 8, "h": 8}, "spriteSourceSize": {"x": 0, "y": 0, "w": 8, "h": 8},
+			"sourceSize": {"w": 8, "h": 8}, "duration": 80}], "meta": {"frameTags": [{"name": "idle", "from": 0, "to": 0, "direction": "pingpong_reverse"}]}}"#;
+		let sheet = SpriteSheet::try_from_aseprite(json).unwrap();
+		assert_eq!(sheet.frame_count(), 1);
+		assert!(sheet.has_tag("idle"));
+		// half saved
+		assert!(SpriteSheet::try_from_aseprite(&json[..json.len() / 2]).is_err());
+		assert!(SpriteSheet::try_from_aseprite("").is_err());
+	}
+}
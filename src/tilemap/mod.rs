@@ -40,12 +40,20 @@ impl Tilemap{
         transform_pyxeltilemap(clip, pyxeltilemap)
     }
 
+    /// panics if the data is not a Tiled map, see try_from_tiled
     #[cfg(feature = "tilemap_xml")]
     pub fn from_tiled(clip: Rectangle, data: &str) -> Tilemap{
         let tiledtilemap = TiledTilemap::new(data);
         transform_tiledtilemap(clip, tiledtilemap)
     }
 
+    /// like from_tiled, but returns a description of the error instead of panicking,
+    /// e.g. for a HotAsset
+    #[cfg(feature = "tilemap_xml")]
+    pub fn try_from_tiled(clip: Rectangle, data: &str) -> Result<Tilemap, String>{
+        TiledTilemap::try_new(data).map(|tiledtilemap| transform_tiledtilemap(clip, tiledtilemap))
+    }
+
     pub fn color(&mut self, color: Color) ->&Tilemap{
         if self.layer_to_draw == -1{
             for mut l in self.layers.iter_mut(){
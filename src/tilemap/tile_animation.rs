@@ -2,6 +2,23 @@ use crate::tilemap::Tilemap;
 use std::time::Duration;
 use tetra::graphics::{Rectangle, Texture, DrawParams};
 use tetra::{Context, time, graphics};
+#[cfg(feature = "ron_file")]
+use serde::{Serialize, Deserialize};
+
+/// frame list of a TileAnimation in a ron file
+#[cfg(feature = "ron_file")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TileAnimationData {
+	pub tile_ids: Vec<u32>,
+	pub durations_ms: Vec<u64>,
+	#[serde(default = "default_repeating")]
+	pub repeating: bool,
+}
+
+#[cfg(feature = "ron_file")]
+fn default_repeating() -> bool{
+	true
+}
 
 pub struct TileAnimation{
 	frames: Vec<Rectangle>,
@@ -41,6 +58,14 @@ impl TileAnimation {
 		}
 	}
 
+	#[cfg(feature = "ron_file")]
+	pub fn from_data(tilemap: &Tilemap, data: &TileAnimationData) -> Self{
+		let durations = data.durations_ms.iter().map(|d| Duration::from_millis(*d)).collect();
+		let mut animation = TileAnimation::new(tilemap, &data.tile_ids, durations);
+		animation.repeating = data.repeating;
+		animation
+	}
+
 	pub fn advance(&mut self, ctx: &Context) {
 		self.advance_by(time::get_delta_time(ctx));
 	}
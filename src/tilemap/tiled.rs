@@ -11,14 +11,20 @@ const FLIP_DIA_2_FLAG: u32   = 2;
 const ALL_FLIP_FLAGS: u32 = 0x8000_0000 | 0x4000_0000 | 0x2000_0000;
 
 impl TiledTilemap {
+    /// panics if the data is not a Tiled map, see try_new
     pub fn new(data: &str) -> TiledTilemap{
+        TiledTilemap::try_new(data).unwrap_or_else(|error| panic!("invalid Tiled map: {}", error))
+    }
+
+    /// parses a Tiled map (tmx or json), returns a description of the error if it can't be parsed
+    pub fn try_new(data: &str) -> Result<TiledTilemap, String>{
         if data.contains("<?xml"){
             info!("create Tilemap from Tiled (tmx).");
-            let mut tmx_tilemap: TmxTilemap = serde_xml_rs::from_str(data).unwrap();
+            let mut tmx_tilemap: TmxTilemap = serde_xml_rs::from_str(data).map_err(|e| e.to_string())?;
             remodel_tmx(&mut tmx_tilemap)
         }else{
             info!("create Tilemap from Tiled (json).");
-            let mut json_tilemap: JsonTilemap = serde_json::from_str(data).unwrap();
+            let mut json_tilemap: JsonTilemap = serde_json::from_str(data).map_err(|e| e.to_string())?;
             remodel_json(&mut json_tilemap)
         }
     }
@@ -184,15 +190,21 @@ fn default_data() -> Vec<u32>{
    Vec::new()
 }
 
-fn remodel_tmx(tilemap: &mut TmxTilemap) -> TiledTilemap{
+fn first_gid(tilesets: &[Tileset]) -> Result<u32, String>{
+    tilesets.first().map(|t| t.firstgid).ok_or_else(|| "the map has no tileset".to_string())
+}
+
+fn remodel_tmx(tilemap: &mut TmxTilemap) -> Result<TiledTilemap, String>{
     let mut layers: Vec<Layer> = vec![];
     let tile_height = tilemap.tile_height;
     let tile_width = tilemap.tile_width;
 
     for l in tilemap.layers.iter(){
         let data = l.data.tile_data.replace("\r\n","");
-        let nums = data.split(',').map(|s| s.parse::<u32>().unwrap()).collect::<Vec<_>>();
-        let firstgid = tilemap.tilesets[0].firstgid;
+        let nums = data.split(',')
+            .map(|s| s.trim().parse::<u32>().map_err(|_| format!("invalid tile id {:?} in layer {}", s, l.name)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let firstgid = first_gid(&tilemap.tilesets)?;
         layers.push(Layer{
             id: l.id,
             name: l.name.clone(),
@@ -202,22 +214,22 @@ fn remodel_tmx(tilemap: &mut TmxTilemap) -> TiledTilemap{
             //properties: vec![]
         });
     }
-    TiledTilemap{
+    Ok(TiledTilemap{
         tileheight: tilemap.height as usize,
         tilewidth: tilemap.width as usize,
         tile_height,
         tile_width,
         layers,
-    }
+    })
 }
 
-fn remodel_json(tilemap: &mut JsonTilemap) -> TiledTilemap{
+fn remodel_json(tilemap: &mut JsonTilemap) -> Result<TiledTilemap, String>{
     let mut layers: Vec<Layer> = vec![];
     let tile_height = tilemap.tile_height;
     let tile_width = tilemap.tile_width;
     for l in tilemap.layers.iter(){
         if l.layer_type.contains("tilelayer"){
-            let firstgid = tilemap.tilesets[0].firstgid;
+            let firstgid = first_gid(&tilemap.tilesets)?;
             layers.push(Layer {
                 id: l.id,
                 name: l.name.clone(),
@@ -229,13 +241,13 @@ fn remodel_json(tilemap: &mut JsonTilemap) -> TiledTilemap{
         }
     }
 
-    TiledTilemap{
+    Ok(TiledTilemap{
         tileheight: tilemap.height as usize,
         tilewidth: tilemap.width as usize,
         tile_height,
         tile_width,
         layers
-    }
+    })
 }
 
 #[allow(clippy::approx_constant)]
@@ -281,3 +293,43 @@ fn creates_tiles(nums: &[u32],width: i64,tile_width: i64, tile_height: i64, firs
     }
     tiles
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JSON: &str = r#"{"version": 1.4, "tiledversion": "1.4.3", "orientation": "orthogonal", "renderorder": "right-down",
+        "width": 2, "height": 2, "tilewidth": 16, "tileheight": 16,
+        "tilesets": [{"firstgid": 1, "source": "tiles.tsx"}],
+        "layers": [{"id": 1, "name": "ground", "type": "tilelayer", "width": 2, "height": 2, "data": [1, 0, 0, 3]}]}"#;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map version="1.4" tiledversion="1.4.3" orientation="orthogonal" renderorder="right-down" width="2" height="2" tilewidth="16" tileheight="16">
+ <tileset firstgid="1" source="tiles.tsx"/>
+ <layer id="1" name="ground" width="2" height="2">
+  <data encoding="csv">
+1,0,
+0,TILE
+</data>
+ </layer>
+</map>"#;
+
+    #[test]
+    fn json_map(){
+        let map = TiledTilemap::try_new(JSON).unwrap();
+        assert_eq!((map.tilewidth, map.tileheight), (2, 2));
+        let tiles = &map.layers[0].tiles;
+        assert_eq!(tiles.len(), 2);
+        assert_eq!((tiles[1].id, tiles[1].x, tiles[1].y), (2, 1, 1));
+        assert_eq!((tiles[1].position_x, tiles[1].position_y), (16.0, 16.0));
+    }
+
+    #[test]
+    fn errors_are_returned(){
+        assert!(TiledTilemap::try_new("{\"version\": 1.4").is_err());
+        assert!(TiledTilemap::try_new(&JSON.replace(r#"{"firstgid": 1, "source": "tiles.tsx"}"#, "")).is_err());
+        let error = TiledTilemap::try_new(TMX).err().unwrap();
+        assert!(error.contains("TILE"), "{}", error);
+        assert!(TiledTilemap::try_new(&TMX.replace("TILE", "3")).is_ok());
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+use tetra::{Context, time};
+
+/// Frame clock which can be paused and scaled.
+/// Use update() with the tetra delta time or tick() for a manual step (e.g. a virtual clock in tests).
+/// Timers and tweens are advanced with the scaled delta: `timer.tick(clock.delta())`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clock{
+	elapsed: Duration,
+	delta: Duration,
+	time_scale: f32,
+	paused: bool,
+}
+
+#[allow(dead_code)]
+impl Clock{
+	pub fn new() -> Clock{
+		Clock{
+			elapsed: Duration::from_secs(0),
+			delta: Duration::from_secs(0),
+			time_scale: 1.0,
+			paused: false,
+		}
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	pub fn tick(&mut self, delta: Duration){
+		if self.paused{
+			self.delta = Duration::from_secs(0);
+		}else{
+			// rounded, mul_f32 truncates and a scale of 1.0 would lose time
+			self.delta = Duration::from_nanos((delta.as_nanos() as f64 * self.time_scale as f64).round() as u64);
+		}
+		self.elapsed += self.delta;
+	}
+
+	/// scaled time of the last tick, zero while paused
+	pub fn delta(&self) -> Duration{
+		self.delta
+	}
+
+	/// scaled time since the clock was created
+	pub fn elapsed(&self) -> Duration{
+		self.elapsed
+	}
+
+	pub fn pause(&mut self){
+		self.paused = true;
+	}
+
+	pub fn resume(&mut self){
+		self.paused = false;
+	}
+
+	pub fn is_paused(&self) -> bool{
+		self.paused
+	}
+
+	/// 1.0 is normal speed, 0.5 half speed, negative values are treated as 0.0
+	pub fn set_time_scale(&mut self, time_scale: f32){
+		self.time_scale = time_scale.max(0.0);
+	}
+
+	pub fn time_scale(&self) -> f32{
+		self.time_scale
+	}
+}
+
+impl Default for Clock{
+	fn default() -> Clock{
+		Clock::new()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn pause_stops_the_time(){
+		let mut clock = Clock::new();
+		clock.tick(Duration::from_millis(16));
+		clock.pause();
+		clock.tick(Duration::from_millis(16));
+		assert_eq!(clock.delta(), Duration::from_secs(0));
+		assert_eq!(clock.elapsed(), Duration::from_millis(16));
+		clock.resume();
+		clock.tick(Duration::from_millis(16));
+		assert_eq!(clock.elapsed(), Duration::from_millis(32));
+	}
+
+	#[test]
+	fn time_scale(){
+		let mut clock = Clock::new();
+		clock.set_time_scale(2.0);
+		clock.tick(Duration::from_millis(10));
+		assert_eq!(clock.delta(), Duration::from_millis(20));
+		clock.set_time_scale(-1.0);
+		assert_eq!(clock.time_scale(), 0.0);
+		clock.tick(Duration::from_millis(10));
+		assert_eq!(clock.delta(), Duration::from_secs(0));
+		assert_eq!(clock.elapsed(), Duration::from_millis(20));
+	}
+}
@@ -3,9 +3,13 @@
 use tetra::ContextBuilder;
 use log::error;
 
+pub mod clock;
+#[cfg(feature = "randomize")]
+pub mod random;
 pub mod ron;
 pub mod timer;
 pub mod vecgrid;
+pub mod watcher;
 
 #[allow(dead_code)]
 #[cfg(feature = "ron_file")]
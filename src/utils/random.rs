@@ -0,0 +1,32 @@
+use std::cell::RefCell;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+thread_local! {
+	static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// seeds the random number generator of tetrapack (e.g. the variants and pitches of a SoundGroup),
+/// the InputRecorder seeds it with the seed of the recording
+pub fn seed(seed: u64){
+	RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+pub(crate) fn with_rng<T, F: FnOnce(&mut StdRng) -> T>(f: F) -> T{
+	RNG.with(|rng| f(&mut rng.borrow_mut()))
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::Rng;
+	use super::*;
+
+	#[test]
+	fn same_seed_same_numbers(){
+		seed(7);
+		let first: Vec<u32> = (0..8).map(|_| with_rng(|rng| rng.gen())).collect();
+		seed(7);
+		let second: Vec<u32> = (0..8).map(|_| with_rng(|rng| rng.gen())).collect();
+		assert_eq!(first, second);
+	}
+}
@@ -2,3 +2,69 @@
 #[cfg(feature = "ron_file")]
 pub use ron::*;
 
+
+#[cfg(feature = "ron_file")]
+use std::{fmt, fs, io, path::Path};
+#[cfg(feature = "ron_file")]
+use serde::de::DeserializeOwned;
+#[cfg(feature = "ron_file")]
+use serde::Serialize;
+
+/// error while loading a ron file from disk
+#[cfg(feature = "ron_file")]
+#[derive(Debug)]
+pub enum RonFileError {
+	Io(io::Error),
+	Ron(ron::error::SpannedError),
+	Write(ron::Error),
+}
+
+#[cfg(feature = "ron_file")]
+impl fmt::Display for RonFileError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			RonFileError::Io(error) => write!(f, "could not read file: {}", error),
+			RonFileError::Ron(error) => write!(f, "could not parse file: {}", error),
+			RonFileError::Write(error) => write!(f, "could not serialize: {}", error),
+		}
+	}
+}
+
+#[cfg(feature = "ron_file")]
+impl std::error::Error for RonFileError {}
+
+#[cfg(feature = "ron_file")]
+impl From<io::Error> for RonFileError {
+	fn from(error: io::Error) -> RonFileError {
+		RonFileError::Io(error)
+	}
+}
+
+#[cfg(feature = "ron_file")]
+impl From<ron::error::SpannedError> for RonFileError {
+	fn from(error: ron::error::SpannedError) -> RonFileError {
+		RonFileError::Ron(error)
+	}
+}
+
+#[cfg(feature = "ron_file")]
+impl From<ron::Error> for RonFileError {
+	fn from(error: ron::Error) -> RonFileError {
+		RonFileError::Write(error)
+	}
+}
+
+/// reads and parses a ron file at runtime, so it can be changed without recompiling
+#[cfg(feature = "ron_file")]
+pub fn load_file<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> Result<T, RonFileError> {
+	let data = fs::read_to_string(path)?;
+	Ok(ron::from_str(&data)?)
+}
+
+/// writes a value as pretty ron file
+#[cfg(feature = "ron_file")]
+pub fn save_file<T: Serialize, P: AsRef<Path>>(path: P, value: &T) -> Result<(), RonFileError> {
+	let data = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+	fs::write(path, data)?;
+	Ok(())
+}
@@ -1,51 +1,140 @@
-use std::time::{Duration, Instant};
-use std::ops::Sub;
+use std::time::Duration;
+use tetra::{Context, time};
 
+/// Timer runs once and returns a value between 0 and 1.
+/// It only advances with update() (tetra delta time) or tick() (manual step).
 #[derive(Debug, Clone, PartialEq)]
 pub struct Timer{
 	duration: Duration,
-	start_time: Instant,
+	elapsed: Duration,
+	paused: bool,
 }
 
 impl Timer{
 	pub fn new(duration_millis: u64)-> Timer{
-		Timer{
-			duration: Duration::from_millis(duration_millis),
-			start_time: Instant::now(),
-		}
+		Timer::from_duration(Duration::from_millis(duration_millis))
 	}
 	pub fn new_sec(duration_sec: u64)-> Timer{
+		Timer::new(duration_sec * 1000)
+	}
+	pub fn from_duration(duration: Duration)-> Timer{
 		Timer{
-			duration: Duration::from_secs(duration_sec),
-			start_time: Instant::now(),
+			duration,
+			elapsed: Duration::from_secs(0),
+			paused: false,
 		}
 	}
 	pub fn advance_by(&mut self, duration: Duration){
-		self.start_time = self.start_time.sub(duration);
+		self.elapsed += duration;
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	pub fn tick(&mut self, delta: Duration){
+		if !self.paused{
+			self.elapsed += delta;
+		}
 	}
 
 	pub fn finished(&self) -> bool{
-		let current_time = Instant::now();
-		let elapsed = current_time - self.start_time;
-		elapsed >= self.duration
+		self.elapsed >= self.duration
+	}
+
+	#[allow(dead_code)]
+	pub fn set_duration(&mut self, duration_sec: u64){
+		self.duration = Duration::from_secs(duration_sec);
+	}
+
+	#[allow(dead_code)]
+	pub fn set_duration_millis(&mut self, duration_millis: u64){
+		self.duration = Duration::from_millis(duration_millis);
+	}
+
+	pub fn duration(&self) -> Duration{
+		self.duration
+	}
+
+	/// time since the start, can be greater than the duration
+	pub fn elapsed(&self) -> Duration{
+		self.elapsed
 	}
 
 	#[allow(dead_code)]
-	pub fn set_duration(&mut self, duration: u64){
-		self.duration = Duration::from_secs(duration);
+	pub fn remaining(&self) -> Duration{
+		self.duration.checked_sub(self.elapsed).unwrap_or_default()
 	}
 
 	pub fn restart(&mut self){
-		self.start_time = Instant::now();
+		self.elapsed = Duration::from_secs(0);
+	}
+
+	#[allow(dead_code)]
+	pub fn pause(&mut self){
+		self.paused = true;
+	}
+
+	#[allow(dead_code)]
+	pub fn resume(&mut self){
+		self.paused = false;
+	}
+
+	#[allow(dead_code)]
+	pub fn is_paused(&self) -> bool{
+		self.paused
 	}
 
 	pub fn value(&self)-> f32{
-		let current_time = Instant::now();
-		let elapsed = current_time - self.start_time;
-		if elapsed < self.duration{
-			1.0 * (100.0 / self.duration.as_millis() as f32 * elapsed.as_millis() as f32) / 100.0
+		if self.elapsed < self.duration{
+			self.elapsed.as_secs_f32() / self.duration.as_secs_f32()
 		}else{
 			1.0
 		}
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::utils::clock::Clock;
+
+	#[test]
+	fn tick_and_pause(){
+		let mut timer = Timer::new(100);
+		timer.tick(Duration::from_millis(40));
+		assert!((timer.value() - 0.4).abs() < 1e-6);
+		timer.pause();
+		timer.tick(Duration::from_millis(100));
+		assert_eq!(timer.elapsed(), Duration::from_millis(40));
+		assert!(!timer.finished());
+		timer.resume();
+		timer.tick(Duration::from_millis(60));
+		assert!(timer.finished());
+		assert_eq!(timer.remaining(), Duration::from_secs(0));
+		assert!((timer.value() - 1.0).abs() < 1e-6);
+	}
+
+	#[test]
+	fn set_duration_in_seconds(){
+		let mut timer = Timer::new(100);
+		timer.set_duration(2);
+		assert_eq!(timer.duration(), Duration::from_secs(2));
+		timer.set_duration_millis(250);
+		assert_eq!(timer.duration(), Duration::from_millis(250));
+	}
+
+	#[test]
+	fn driven_by_a_scaled_clock(){
+		let mut clock = Clock::new();
+		let mut timer = Timer::new(100);
+		clock.set_time_scale(0.5);
+		clock.tick(Duration::from_millis(100));
+		timer.tick(clock.delta());
+		assert_eq!(timer.elapsed(), Duration::from_millis(50));
+		assert!(!timer.finished());
+		clock.tick(Duration::from_millis(100));
+		timer.tick(clock.delta());
+		assert!(timer.finished());
+	}
+}
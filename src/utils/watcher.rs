@@ -0,0 +1,341 @@
+use std::{fmt, fs, io};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tetra::{Context, time};
+use log::{debug, error};
+#[cfg(feature = "ron_file")]
+use serde::de::DeserializeOwned;
+
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetEvent {
+	Changed(PathBuf),
+	Removed(PathBuf),
+}
+
+impl AssetEvent {
+	pub fn path(&self) -> &Path{
+		match self{
+			AssetEvent::Changed(path) => path,
+			AssetEvent::Removed(path) => path,
+		}
+	}
+}
+
+// modification time and length, the length catches writes within the resolution of the file system
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+	modified: SystemTime,
+	len: u64,
+}
+
+fn stamp(path: &Path) -> Option<FileStamp>{
+	let metadata = fs::metadata(path).ok()?;
+	Some(FileStamp{
+		modified: metadata.modified().ok()?,
+		len: metadata.len(),
+	})
+}
+
+#[derive(Debug, Clone)]
+struct WatchedFile {
+	path: PathBuf,
+	stamp: Option<FileStamp>,
+}
+
+/// Polls the modification time of files during development, no file system notifications are needed.
+/// Changes are collected as events, the SceneManager passes them to Scene::reload.
+#[derive(Debug, Clone)]
+pub struct AssetWatcher {
+	files: Vec<WatchedFile>,
+	interval: Duration,
+	since_check: Duration,
+	events: Vec<AssetEvent>,
+}
+
+#[allow(dead_code)]
+impl AssetWatcher {
+	pub fn new() -> AssetWatcher{
+		AssetWatcher{
+			files: Vec::new(),
+			interval: DEFAULT_INTERVAL,
+			since_check: Duration::from_secs(0),
+			events: Vec::new(),
+		}
+	}
+
+	/// time between two checks, default is 500ms
+	pub fn interval(mut self, interval: Duration) -> Self{
+		self.interval = interval;
+		self
+	}
+
+	pub fn watch<P: AsRef<Path>>(&mut self, path: P){
+		let path = path.as_ref().to_path_buf();
+		if !self.is_watched(&path){
+			debug!("watch {}", path.display());
+			self.files.push(WatchedFile{
+				stamp: stamp(&path),
+				path,
+			});
+		}
+	}
+
+	pub fn unwatch<P: AsRef<Path>>(&mut self, path: P){
+		self.files.retain(|f| f.path != path.as_ref());
+	}
+
+	pub fn is_watched<P: AsRef<Path>>(&self, path: P) -> bool{
+		self.files.iter().any(|f| f.path == path.as_ref())
+	}
+
+	pub fn update(&mut self, ctx: &Context){
+		self.tick(time::get_delta_time(ctx));
+	}
+
+	/// checks the files when the interval has passed
+	pub fn tick(&mut self, delta: Duration){
+		self.since_check += delta;
+		if self.since_check >= self.interval{
+			self.since_check = Duration::from_secs(0);
+			self.check();
+		}
+	}
+
+	/// checks all files immediately
+	pub fn check(&mut self){
+		for file in self.files.iter_mut(){
+			let current = stamp(&file.path);
+			if current == file.stamp{
+				continue;
+			}
+			match current{
+				Some(_) => {
+					debug!("{} changed", file.path.display());
+					self.events.push(AssetEvent::Changed(file.path.clone()));
+				}
+				None => {
+					debug!("{} removed", file.path.display());
+					self.events.push(AssetEvent::Removed(file.path.clone()));
+				}
+			}
+			file.stamp = current;
+		}
+	}
+
+	/// events since the last call
+	pub fn events(&mut self) -> Vec<AssetEvent>{
+		std::mem::take(&mut self.events)
+	}
+}
+
+impl Default for AssetWatcher {
+	fn default() -> AssetWatcher {
+		AssetWatcher::new()
+	}
+}
+
+/// error while loading a HotAsset
+#[derive(Debug)]
+pub enum AssetError {
+	Io(io::Error),
+	#[cfg(feature = "ron_file")]
+	Ron(ron::error::SpannedError),
+	/// the loader failed, e.g. a parse error of another format
+	Load(String),
+}
+
+impl fmt::Display for AssetError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			AssetError::Io(error) => write!(f, "could not read file: {}", error),
+			#[cfg(feature = "ron_file")]
+			AssetError::Ron(error) => write!(f, "could not parse file: {}", error),
+			AssetError::Load(error) => write!(f, "could not load asset: {}", error),
+		}
+	}
+}
+
+impl std::error::Error for AssetError {}
+
+impl From<io::Error> for AssetError {
+	fn from(error: io::Error) -> AssetError {
+		AssetError::Io(error)
+	}
+}
+
+#[cfg(feature = "ron_file")]
+impl From<ron::error::SpannedError> for AssetError {
+	fn from(error: ron::error::SpannedError) -> AssetError {
+		AssetError::Ron(error)
+	}
+}
+
+impl From<String> for AssetError {
+	fn from(error: String) -> AssetError {
+		AssetError::Load(error)
+	}
+}
+
+impl From<&str> for AssetError {
+	fn from(error: &str) -> AssetError {
+		AssetError::Load(error.to_string())
+	}
+}
+
+#[cfg(any(feature = "aseprite", feature = "tilemap_json"))]
+impl From<serde_json::Error> for AssetError {
+	fn from(error: serde_json::Error) -> AssetError {
+		AssetError::Load(error.to_string())
+	}
+}
+
+type Loader<T> = Box<dyn Fn(&str) -> Result<T, AssetError>>;
+
+/// An asset which is loaded from a file and can be loaded again when the file changes.
+/// If the new content can't be loaded, the error is logged and the old value is kept.
+pub struct HotAsset<T> {
+	path: PathBuf,
+	loader: Loader<T>,
+	value: T,
+}
+
+#[allow(dead_code)]
+impl<T> HotAsset<T> {
+	/// e.g. HotAsset::new("assets/map.json", move |data| Tilemap::try_from_tiled(clip, data))
+	/// or HotAsset::new("assets/hero.json", SpriteSheet::try_from_aseprite),
+	/// the error of the loader is converted into an AssetError, e.g. a String or a serde_json::Error
+	pub fn new<P, F, E>(path: P, loader: F) -> Result<HotAsset<T>, AssetError>
+		where
+			P: AsRef<Path>,
+			F: Fn(&str) -> Result<T, E> + 'static,
+			E: Into<AssetError>,
+	{
+		let loader: Loader<T> = Box::new(move |data| loader(data).map_err(|e| e.into()));
+		let path = path.as_ref().to_path_buf();
+		let data = fs::read_to_string(&path)?;
+		let value = loader(&data)?;
+		Ok(HotAsset{
+			path,
+			loader,
+			value,
+		})
+	}
+
+	pub fn path(&self) -> &Path{
+		&self.path
+	}
+
+	pub fn get(&self) -> &T{
+		&self.value
+	}
+
+	pub fn get_mut(&mut self) -> &mut T{
+		&mut self.value
+	}
+
+	/// the old value is kept if the file can't be loaded
+	pub fn reload(&mut self) -> Result<(), AssetError>{
+		let data = fs::read_to_string(&self.path)?;
+		self.value = (self.loader)(&data)?;
+		debug!("reloaded {}", self.path.display());
+		Ok(())
+	}
+
+	/// reloads if the event belongs to this asset, returns true if it was reloaded, errors are logged
+	pub fn on_event(&mut self, event: &AssetEvent) -> bool{
+		match event{
+			AssetEvent::Changed(path) if *path == self.path => match self.reload(){
+				Ok(()) => true,
+				Err(error) => {
+					error!("Failed to reload {}: {}", self.path.display(), error);
+					false
+				}
+			},
+			_ => false,
+		}
+	}
+
+	pub fn watch(&self, watcher: &mut AssetWatcher){
+		watcher.watch(&self.path);
+	}
+}
+
+#[cfg(feature = "ron_file")]
+#[allow(dead_code)]
+impl<T: DeserializeOwned + 'static> HotAsset<T> {
+	/// e.g. HotAsset::<Config>::ron("config.ron") or HotAsset::<AnimationData>::ron("animations.ron")
+	pub fn ron<P: AsRef<Path>>(path: P) -> Result<HotAsset<T>, AssetError>{
+		HotAsset::new(path, |data| ron::from_str::<T>(data))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// a new file in the temp directory which is removed at the end of the test
+	struct TempFile(PathBuf);
+
+	impl TempFile {
+		fn new(name: &str, content: &str) -> TempFile{
+			let path = std::env::temp_dir().join(format!("tetrapack_{}_{}", std::process::id(), name));
+			fs::write(&path, content).unwrap();
+			TempFile(path)
+		}
+	}
+
+	impl Drop for TempFile {
+		fn drop(&mut self){
+			let _ = fs::remove_file(&self.0);
+		}
+	}
+
+	fn parse(data: &str) -> Result<i32, String>{
+		data.trim().parse().map_err(|_| format!("{} is not a number", data))
+	}
+
+	#[test]
+	fn poll_and_reload(){
+		let file = TempFile::new("poll.txt", "1");
+		let mut asset = HotAsset::new(&file.0, parse).unwrap();
+		let mut watcher = AssetWatcher::new().interval(Duration::from_millis(100));
+		asset.watch(&mut watcher);
+		watcher.tick(Duration::from_millis(100));
+		assert!(watcher.events().is_empty());
+
+		// a different length is detected even within the resolution of the modification time
+		fs::write(&file.0, "22").unwrap();
+		watcher.tick(Duration::from_millis(50));
+		assert!(watcher.events().is_empty());
+		watcher.tick(Duration::from_millis(50));
+		let events = watcher.events();
+		assert_eq!(events, vec![AssetEvent::Changed(file.0.clone())]);
+		assert!(asset.on_event(&events[0]));
+		assert_eq!(*asset.get(), 22);
+
+		fs::remove_file(&file.0).unwrap();
+		watcher.check();
+		assert_eq!(watcher.events(), vec![AssetEvent::Removed(file.0.clone())]);
+	}
+
+	#[test]
+	fn errors_keep_the_old_value(){
+		let file = TempFile::new("errors.txt", "1");
+		let mut asset = HotAsset::new(&file.0, parse).unwrap();
+		fs::write(&file.0, "one").unwrap();
+		assert!(matches!(asset.reload(), Err(AssetError::Load(_))));
+		fs::remove_file(&file.0).unwrap();
+		assert!(matches!(asset.reload(), Err(AssetError::Io(_))));
+		assert_eq!(*asset.get(), 1);
+	}
+
+	#[cfg(feature = "aseprite")]
+	#[test]
+	fn parse_errors_of_a_loader(){
+		use crate::sprite::SpriteSheet;
+		let file = TempFile::new("sheet.json", r#"{"frames": ["#);
+		assert!(matches!(HotAsset::new(&file.0, SpriteSheet::try_from_aseprite), Err(AssetError::Load(_))));
+	}
+}